@@ -0,0 +1,348 @@
+use std::time::{Duration, Instant, SystemTime};
+
+use crate::config::AlertsConfig;
+use crate::core::{Comparison, Condition, MetricPath, MetricValue, Severity};
+use crate::engine::MetricsSnapshot;
+use crate::error::{GimError, Result};
+
+/// A threshold rule, written `memory.memory_usage_percent > 90 for 2m => critical`.
+/// An optional `clear <value>` overrides the hysteresis-derived resolve level.
+#[derive(Debug, Clone)]
+pub struct AlertRule {
+    pub text: String,
+    pub condition: Condition,
+    pub duration: Duration,
+    pub clear: Option<f64>,
+    pub severity: Severity,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AlertState {
+    Pending,
+    Firing,
+    Resolved,
+}
+
+impl AlertState {
+    pub fn label(&self) -> &'static str {
+        match self {
+            AlertState::Pending => "pending",
+            AlertState::Firing => "firing",
+            AlertState::Resolved => "resolved",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Alert {
+    pub rule: String,
+    pub path: MetricPath,
+    pub severity: Severity,
+    pub state: AlertState,
+    pub value: f64,
+    pub since: SystemTime,
+    /// True on the sample where the alert started firing or resolved.
+    pub changed: bool,
+}
+
+impl Alert {
+    pub fn severity_label(&self) -> &'static str {
+        match self.severity {
+            Severity::Ok => "ok",
+            Severity::Warning => "warning",
+            Severity::Critical => "critical",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum RuleState {
+    Inactive,
+    Pending { since: Instant, wall: SystemTime },
+    Firing { wall: SystemTime },
+}
+
+pub struct AlertEvaluator {
+    rules: Vec<AlertRule>,
+    states: Vec<RuleState>,
+    hysteresis_percent: f64,
+}
+
+impl AlertEvaluator {
+    pub fn from_config(config: &AlertsConfig) -> Result<Self> {
+        let rules = config
+            .rules
+            .iter()
+            .map(|text| AlertRule::parse(text).ok_or_else(|| GimError::InvalidRule(text.clone())))
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Self {
+            states: vec![RuleState::Inactive; rules.len()],
+            rules,
+            hysteresis_percent: config.hysteresis_percent,
+        })
+    }
+
+    /// Advances every rule by one sample and returns the alerts that are pending, firing
+    /// or resolved on this sample.
+    pub fn evaluate(&mut self, snapshot: &MetricsSnapshot) -> Vec<Alert> {
+        let now = Instant::now();
+        let wall = SystemTime::now();
+        let mut alerts = Vec::new();
+
+        for (rule, state) in self.rules.iter().zip(self.states.iter_mut()) {
+            // A metric that was not collected this round leaves the rule where it was.
+            let Some(value) = snapshot
+                .get(&rule.condition.path)
+                .and_then(MetricValue::as_f64)
+            else {
+                continue;
+            };
+
+            let alert = |state: AlertState, since: SystemTime, changed: bool| Alert {
+                rule: rule.text.clone(),
+                path: rule.condition.path.clone(),
+                severity: rule.severity,
+                state,
+                value,
+                since,
+                changed,
+            };
+
+            match *state {
+                RuleState::Inactive if rule.condition.holds(value) => {
+                    if rule.duration.is_zero() {
+                        *state = RuleState::Firing { wall };
+                        alerts.push(alert(AlertState::Firing, wall, true));
+                    } else {
+                        *state = RuleState::Pending { since: now, wall };
+                        alerts.push(alert(AlertState::Pending, wall, false));
+                    }
+                }
+                RuleState::Inactive => {}
+                RuleState::Pending {
+                    since,
+                    wall: started,
+                } => {
+                    if !rule.condition.holds(value) {
+                        *state = RuleState::Inactive;
+                    } else if now.duration_since(since) >= rule.duration {
+                        *state = RuleState::Firing { wall };
+                        alerts.push(alert(AlertState::Firing, wall, true));
+                    } else {
+                        alerts.push(alert(AlertState::Pending, started, false));
+                    }
+                }
+                RuleState::Firing { wall: started } => {
+                    if rule.is_cleared(value, self.hysteresis_percent) {
+                        *state = RuleState::Inactive;
+                        alerts.push(alert(AlertState::Resolved, wall, true));
+                    } else {
+                        alerts.push(alert(AlertState::Firing, started, false));
+                    }
+                }
+            }
+        }
+
+        alerts
+    }
+}
+
+impl AlertRule {
+    pub fn parse(text: &str) -> Option<Self> {
+        let (lhs, severity) = text.split_once("=>")?;
+        let severity = match severity.trim().to_lowercase().as_str() {
+            "warning" | "warn" => Severity::Warning,
+            "critical" | "crit" => Severity::Critical,
+            _ => return None,
+        };
+
+        let (lhs, clear) = match lhs.split_once(" clear ") {
+            Some((lhs, clear)) => (lhs, Some(clear.trim().parse().ok()?)),
+            None => (lhs, None),
+        };
+        let (condition, duration) = match lhs.split_once(" for ") {
+            Some((condition, duration)) => (condition, parse_duration(duration.trim())?),
+            None => (lhs, Duration::ZERO),
+        };
+
+        Some(Self {
+            text: text.trim().to_string(),
+            condition: Condition::parse(condition)?,
+            duration,
+            clear,
+            severity,
+        })
+    }
+
+    /// A firing alert resolves only once the value is back past the clear level, which
+    /// defaults to the threshold moved by `hysteresis_percent` of itself.
+    fn is_cleared(&self, value: f64, hysteresis_percent: f64) -> bool {
+        let threshold = self.condition.value;
+        let margin = threshold.abs() * hysteresis_percent / 100.0;
+
+        match self.condition.op {
+            Comparison::Greater | Comparison::GreaterEqual => {
+                value < self.clear.unwrap_or(threshold - margin)
+            }
+            Comparison::Less | Comparison::LessEqual => {
+                value > self.clear.unwrap_or(threshold + margin)
+            }
+            Comparison::Equal | Comparison::NotEqual => !self.condition.holds(value),
+        }
+    }
+}
+
+/// Parses `90s`, `2m`, `1h`, `500ms`; a bare number is taken as seconds.
+pub fn parse_duration(s: &str) -> Option<Duration> {
+    let split = s
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(s.len());
+    let (number, unit) = s.split_at(split);
+    let number: f64 = number.parse().ok()?;
+
+    let secs = match unit.trim() {
+        "ms" => number / 1000.0,
+        "" | "s" => number,
+        "m" => number * 60.0,
+        "h" => number * 3600.0,
+        _ => return None,
+    };
+    Duration::try_from_secs_f64(secs).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::AlertsConfig;
+    use crate::core::MetricData;
+
+    fn rule(text: &str) -> AlertRule {
+        AlertRule::parse(text).unwrap()
+    }
+
+    fn snapshot(value: f64) -> MetricsSnapshot {
+        MetricsSnapshot {
+            modules: vec![(
+                "memory".to_string(),
+                MetricData {
+                    timestamp: SystemTime::now(),
+                    metrics: [(
+                        "memory_usage_percent".to_string(),
+                        MetricValue::Float(value),
+                    )]
+                    .into_iter()
+                    .collect(),
+                },
+            )],
+            alerts: Vec::new(),
+            anomalies: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn parses_rules() {
+        let r = rule("memory.memory_usage_percent > 90 for 2m => critical");
+        assert_eq!(r.condition.path.to_string(), "memory.memory_usage_percent");
+        assert_eq!(r.condition.op, Comparison::Greater);
+        assert_eq!(r.condition.value, 90.0);
+        assert_eq!(r.duration, Duration::from_secs(120));
+        assert_eq!(r.clear, None);
+        assert_eq!(r.severity, Severity::Critical);
+
+        let r = rule("disk.usage_percent>=80 clear 70 => WARN");
+        assert_eq!(r.duration, Duration::ZERO);
+        assert_eq!(r.clear, Some(70.0));
+        assert_eq!(r.severity, Severity::Warning);
+
+        let r = rule("memory.available_memory_bytes < 1000 for 30s clear 2000 => crit");
+        assert_eq!(r.condition.op, Comparison::Less);
+        assert_eq!(r.duration, Duration::from_secs(30));
+        assert_eq!(r.clear, Some(2000.0));
+    }
+
+    #[test]
+    fn rejects_malformed_rules() {
+        for bad in [
+            "memory.memory_usage_percent > 90",
+            "memory.memory_usage_percent > 90 => fatal",
+            "memory.memory_usage_percent > 90 for soon => warning",
+            "memory.memory_usage_percent > 90 clear high => warning",
+            "memory > 90 => warning",
+        ] {
+            assert!(AlertRule::parse(bad).is_none(), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn parses_durations() {
+        assert_eq!(parse_duration("90s"), Some(Duration::from_secs(90)));
+        assert_eq!(parse_duration("2m"), Some(Duration::from_secs(120)));
+        assert_eq!(parse_duration("1h"), Some(Duration::from_secs(3600)));
+        assert_eq!(parse_duration("500ms"), Some(Duration::from_millis(500)));
+        assert_eq!(parse_duration("1.5m"), Some(Duration::from_secs(90)));
+        assert_eq!(parse_duration("45"), Some(Duration::from_secs(45)));
+        assert_eq!(parse_duration("2d"), None);
+        assert_eq!(parse_duration("m"), None);
+        assert_eq!(parse_duration(""), None);
+    }
+
+    #[test]
+    fn hysteresis_delays_clearing() {
+        // 10% of 90 puts the default clear level at 81.
+        let above = rule("memory.memory_usage_percent > 90 => warning");
+        assert!(!above.is_cleared(85.0, 10.0));
+        assert!(!above.is_cleared(81.0, 10.0));
+        assert!(above.is_cleared(80.9, 10.0));
+        assert!(above.is_cleared(89.0, 0.0));
+
+        let below = rule("memory.available_memory_bytes < 1000 => warning");
+        assert!(!below.is_cleared(1050.0, 10.0));
+        assert!(below.is_cleared(1101.0, 10.0));
+
+        // An explicit clear level wins over the percentage.
+        let explicit = rule("memory.memory_usage_percent > 90 clear 50 => warning");
+        assert!(!explicit.is_cleared(60.0, 10.0));
+        assert!(explicit.is_cleared(49.0, 10.0));
+
+        let equal = rule("memory.memory_usage_percent == 1 => warning");
+        assert!(equal.is_cleared(0.0, 10.0));
+        assert!(!equal.is_cleared(1.0, 10.0));
+    }
+
+    #[test]
+    fn fires_and_resolves_past_the_clear_level() {
+        let mut evaluator = AlertEvaluator::from_config(&AlertsConfig {
+            rules: vec!["memory.memory_usage_percent > 90 => critical".to_string()],
+            hysteresis_percent: 10.0,
+        })
+        .unwrap();
+        let mut states = |value: f64| -> Vec<(AlertState, bool)> {
+            evaluator
+                .evaluate(&snapshot(value))
+                .iter()
+                .map(|a| (a.state, a.changed))
+                .collect()
+        };
+
+        assert_eq!(states(50.0), vec![]);
+        assert_eq!(states(95.0), vec![(AlertState::Firing, true)]);
+        assert_eq!(states(85.0), vec![(AlertState::Firing, false)]);
+        assert_eq!(states(80.0), vec![(AlertState::Resolved, true)]);
+        assert_eq!(states(80.0), vec![]);
+    }
+
+    #[test]
+    fn waits_for_the_duration_before_firing() {
+        let mut evaluator = AlertEvaluator::from_config(&AlertsConfig {
+            rules: vec!["memory.memory_usage_percent > 90 for 1h => warning".to_string()],
+            hysteresis_percent: 10.0,
+        })
+        .unwrap();
+
+        let alerts = evaluator.evaluate(&snapshot(95.0));
+        assert_eq!(alerts[0].state, AlertState::Pending);
+        // Dropping below the threshold before the hour is up cancels it silently.
+        assert!(evaluator.evaluate(&snapshot(50.0)).is_empty());
+    }
+}
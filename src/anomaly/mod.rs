@@ -0,0 +1,268 @@
+use std::collections::{HashMap, VecDeque};
+
+use crate::config::{glob_match, AnomalyConfig, AnomalyMethod};
+use crate::core::MetricPath;
+use crate::engine::MetricsSnapshot;
+
+/// A sample that sits outside the band learned from the metric's recent history.
+#[derive(Debug, Clone)]
+pub struct Anomaly {
+    pub path: MetricPath,
+    pub value: f64,
+    pub baseline: f64,
+    pub score: f64,
+}
+
+impl Anomaly {
+    pub fn direction(&self) -> &'static str {
+        if self.score >= 0.0 {
+            "high"
+        } else {
+            "low"
+        }
+    }
+}
+
+#[derive(Default)]
+struct Series {
+    samples: usize,
+    mean: f64,
+    variance: f64,
+    window: VecDeque<f64>,
+}
+
+pub struct AnomalyDetector {
+    config: AnomalyConfig,
+    series: HashMap<MetricPath, Series>,
+}
+
+impl AnomalyDetector {
+    pub fn new(config: AnomalyConfig) -> Self {
+        Self {
+            config,
+            series: HashMap::new(),
+        }
+    }
+
+    /// Scores every watched metric against its history, then folds the sample into it.
+    pub fn evaluate(&mut self, snapshot: &MetricsSnapshot) -> Vec<Anomaly> {
+        if self.config.metrics.is_empty() {
+            return Vec::new();
+        }
+
+        let mut anomalies = Vec::new();
+        for (module, data) in &snapshot.modules {
+            for (key, value) in &data.metrics {
+                let Some(value) = value.as_f64().filter(|v| v.is_finite()) else {
+                    continue;
+                };
+                let full = format!("{}.{}", module, key);
+                if !self.config.metrics.iter().any(|p| glob_match(p, &full)) {
+                    continue;
+                }
+
+                let path = MetricPath {
+                    module: module.clone(),
+                    key: key.clone(),
+                };
+                let series = self.series.entry(path.clone()).or_default();
+
+                if series.samples >= self.config.warmup {
+                    let scored = match self.config.method {
+                        AnomalyMethod::Ewma => series.ewma_score(value),
+                        AnomalyMethod::Mad => series.mad_score(value),
+                    };
+                    if let Some((baseline, score)) = scored {
+                        if score.abs() > self.config.threshold {
+                            anomalies.push(Anomaly {
+                                path,
+                                value,
+                                baseline,
+                                score,
+                            });
+                        }
+                    }
+                }
+
+                series.update(value, self.config.alpha, self.config.window);
+            }
+        }
+
+        anomalies.sort_by(|a, b| a.path.cmp(&b.path));
+        anomalies
+    }
+}
+
+impl Series {
+    fn update(&mut self, value: f64, alpha: f64, window: usize) {
+        if self.samples == 0 {
+            self.mean = value;
+            self.variance = 0.0;
+        } else {
+            let diff = value - self.mean;
+            let increment = alpha * diff;
+            self.mean += increment;
+            self.variance = (1.0 - alpha) * (self.variance + diff * increment);
+        }
+        self.samples += 1;
+
+        self.window.push_back(value);
+        while self.window.len() > window.max(1) {
+            self.window.pop_front();
+        }
+    }
+
+    /// Distance from the exponentially weighted mean in exponentially weighted
+    /// standard deviations.
+    fn ewma_score(&self, value: f64) -> Option<(f64, f64)> {
+        let stddev = self.variance.sqrt();
+        if stddev <= f64::EPSILON {
+            return None;
+        }
+        Some((self.mean, (value - self.mean) / stddev))
+    }
+
+    /// Modified z-score against the window's median and median absolute deviation,
+    /// which a few earlier outliers cannot drag around the way they drag a mean.
+    fn mad_score(&self, value: f64) -> Option<(f64, f64)> {
+        let mut values: Vec<f64> = self.window.iter().copied().collect();
+        let center = median(&mut values)?;
+
+        let mut deviations: Vec<f64> = values.iter().map(|v| (v - center).abs()).collect();
+        let mad = median(&mut deviations)?;
+
+        // With more than half the window identical the MAD is zero; fall back to the
+        // mean absolute deviation, scaled to match.
+        let spread = if mad > f64::EPSILON {
+            mad / 0.6745
+        } else {
+            let mean_dev = deviations.iter().sum::<f64>() / deviations.len() as f64;
+            mean_dev * 1.2533
+        };
+        if spread <= f64::EPSILON {
+            return None;
+        }
+        Some((center, (value - center) / spread))
+    }
+}
+
+fn median(values: &mut [f64]) -> Option<f64> {
+    if values.is_empty() {
+        return None;
+    }
+    values.sort_by(|a, b| a.total_cmp(b));
+    let mid = values.len() / 2;
+    if values.len().is_multiple_of(2) {
+        Some((values[mid - 1] + values[mid]) / 2.0)
+    } else {
+        Some(values[mid])
+    }
+}
+
+/// Convenience for callers that only need to know whether a metric was flagged.
+pub fn is_flagged(anomalies: &[Anomaly], module: &str, key: &str) -> bool {
+    anomalies
+        .iter()
+        .any(|a| a.path.module == module && a.path.key == key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{MetricData, MetricValue};
+    use std::time::SystemTime;
+
+    fn series(values: &[f64], alpha: f64, window: usize) -> Series {
+        let mut series = Series::default();
+        for value in values {
+            series.update(*value, alpha, window);
+        }
+        series
+    }
+
+    fn close(actual: f64, expected: f64) -> bool {
+        (actual - expected).abs() < 1e-9
+    }
+
+    #[test]
+    fn ewma_scores_in_weighted_standard_deviations() {
+        // alpha 0.5: the mean moves halfway to 12 and the variance becomes 1.
+        let s = series(&[10.0, 12.0], 0.5, 10);
+        let (baseline, score) = s.ewma_score(14.0).unwrap();
+        assert!(close(baseline, 11.0));
+        assert!(close(score, 3.0));
+        assert!(close(s.ewma_score(10.0).unwrap().1, -1.0));
+
+        // A flat history has no spread to measure against.
+        assert!(series(&[5.0, 5.0, 5.0], 0.5, 10).ewma_score(9.0).is_none());
+    }
+
+    #[test]
+    fn mad_scores_ignore_earlier_outliers() {
+        let s = series(&[1.0, 2.0, 3.0, 4.0, 100.0], 0.1, 10);
+        let (baseline, score) = s.mad_score(5.0).unwrap();
+        assert!(close(baseline, 3.0));
+        assert!(close(score, 2.0 * 0.6745));
+    }
+
+    #[test]
+    fn mad_falls_back_to_mean_deviation() {
+        // More than half the window is identical, so the MAD itself is zero.
+        let s = series(&[5.0, 5.0, 5.0, 6.0], 0.1, 10);
+        let (baseline, score) = s.mad_score(6.0).unwrap();
+        assert!(close(baseline, 5.0));
+        assert!(close(score, 1.0 / (0.25 * 1.2533)));
+
+        assert!(series(&[5.0; 4], 0.1, 10).mad_score(6.0).is_none());
+    }
+
+    #[test]
+    fn window_keeps_the_latest_samples() {
+        let s = series(&[1.0, 2.0, 3.0, 4.0], 0.1, 2);
+        assert_eq!(s.window, [3.0, 4.0]);
+        assert_eq!(s.samples, 4);
+    }
+
+    #[test]
+    fn medians() {
+        assert_eq!(median(&mut []), None);
+        assert_eq!(median(&mut [3.0, 1.0, 2.0]), Some(2.0));
+        assert_eq!(median(&mut [4.0, 1.0, 3.0, 2.0]), Some(2.5));
+    }
+
+    #[test]
+    fn flags_outliers_after_warmup() {
+        let mut detector = AnomalyDetector::new(AnomalyConfig {
+            metrics: vec!["cpu.*".to_string()],
+            method: AnomalyMethod::Mad,
+            threshold: 3.0,
+            warmup: 4,
+            ..AnomalyConfig::default()
+        });
+        let sample = |value: f64| MetricsSnapshot {
+            modules: vec![(
+                "cpu".to_string(),
+                MetricData {
+                    timestamp: SystemTime::now(),
+                    metrics: [("usage_percent".to_string(), MetricValue::Float(value))]
+                        .into_iter()
+                        .collect(),
+                },
+            )],
+            alerts: Vec::new(),
+            anomalies: Vec::new(),
+        };
+
+        // Still warming up, so even a wild value passes.
+        for value in [10.0, 11.0, 90.0, 10.0] {
+            assert!(detector.evaluate(&sample(value)).is_empty());
+        }
+        assert!(detector.evaluate(&sample(12.0)).is_empty());
+
+        let anomalies = detector.evaluate(&sample(95.0));
+        assert_eq!(anomalies.len(), 1);
+        assert_eq!(anomalies[0].path.to_string(), "cpu.usage_percent");
+        assert_eq!(anomalies[0].direction(), "high");
+        assert!(anomalies[0].score > 3.0);
+    }
+}
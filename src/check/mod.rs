@@ -0,0 +1,387 @@
+use crate::config::Config;
+use crate::core::{Comparison, Condition, MetricPath, MetricValue};
+use crate::engine::{Engine, MetricsSnapshot};
+use crate::error::{GimError, Result};
+
+/// Monitoring plugin states, ordered by severity. The discriminants are the exit codes
+/// Nagios and Icinga expect.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum CheckStatus {
+    Ok = 0,
+    Warning = 1,
+    Critical = 2,
+    Unknown = 3,
+}
+
+impl CheckStatus {
+    pub fn code(&self) -> i32 {
+        *self as i32
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            CheckStatus::Ok => "OK",
+            CheckStatus::Warning => "WARNING",
+            CheckStatus::Critical => "CRITICAL",
+            CheckStatus::Unknown => "UNKNOWN",
+        }
+    }
+}
+
+impl std::fmt::Display for CheckStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.label())
+    }
+}
+
+pub fn parse_conditions(conditions: &[String]) -> Result<Vec<Condition>> {
+    conditions
+        .iter()
+        .map(|c| Condition::parse(c).ok_or_else(|| GimError::InvalidCondition(c.clone())))
+        .collect()
+}
+
+/// Modules needed to evaluate the given conditions, in first-seen order.
+pub fn required_modules(conditions: &[Condition]) -> Vec<String> {
+    let mut modules: Vec<String> = Vec::new();
+    for condition in conditions {
+        if !modules.contains(&condition.path.module) {
+            modules.push(condition.path.module.clone());
+        }
+    }
+    modules
+}
+
+/// Collects once and returns the plugin status and its status line.
+pub fn run_check(
+    config: &Config,
+    warn: &[String],
+    crit: &[String],
+) -> Result<(CheckStatus, String)> {
+    let warn = parse_conditions(warn)?;
+    let crit = parse_conditions(crit)?;
+    if warn.is_empty() && crit.is_empty() {
+        return Err(unknown("no --warn or --crit conditions given"));
+    }
+
+    let mut conditions = crit.clone();
+    conditions.extend(warn.iter().cloned());
+    let mut engine = Engine::new(&required_modules(&conditions), config)?;
+    let snapshot = engine.collect_once();
+    Ok(evaluate(&snapshot, &warn, &crit))
+}
+
+/// Prints the plugin status line for the outcome of a check, with any error before or
+/// during it reported as UNKNOWN, and returns the matching `GimError::Check` for anything
+/// other than OK. This is the only place check mode writes to stdout.
+pub fn report(outcome: Result<(CheckStatus, String)>) -> Result<()> {
+    let (status, line) = match outcome {
+        Ok(result) => result,
+        Err(GimError::Check { status, message }) => (status, message),
+        Err(other) => (CheckStatus::Unknown, unknown_line(&other.to_string())),
+    };
+    println!("{}", line);
+
+    match status {
+        CheckStatus::Ok => Ok(()),
+        status => Err(GimError::Check {
+            status,
+            message: line,
+        }),
+    }
+}
+
+pub fn unknown(reason: &str) -> GimError {
+    GimError::Check {
+        status: CheckStatus::Unknown,
+        message: unknown_line(reason),
+    }
+}
+
+fn unknown_line(reason: &str) -> String {
+    format!("GIM UNKNOWN - {}", reason)
+}
+
+fn evaluate(
+    snapshot: &MetricsSnapshot,
+    warn: &[Condition],
+    crit: &[Condition],
+) -> (CheckStatus, String) {
+    let mut status = CheckStatus::Ok;
+    let mut problems: Vec<(CheckStatus, String)> = Vec::new();
+
+    let checks = crit
+        .iter()
+        .map(|c| (CheckStatus::Critical, c))
+        .chain(warn.iter().map(|c| (CheckStatus::Warning, c)));
+
+    for (level, condition) in checks {
+        match snapshot.get(&condition.path).and_then(MetricValue::as_f64) {
+            Some(value) if condition.holds(value) => {
+                problems.push((
+                    level,
+                    format!(
+                        "{}={} ({}{})",
+                        condition.path,
+                        format_value(value),
+                        condition.op.symbol(),
+                        format_value(condition.value)
+                    ),
+                ));
+                status = status.max(level);
+            }
+            Some(_) => {}
+            None => {
+                problems.push((
+                    CheckStatus::Unknown,
+                    format!("{} not available", condition.path),
+                ));
+                status = status.max(CheckStatus::Unknown);
+            }
+        }
+    }
+
+    let summary = if problems.is_empty() {
+        let count = warn.len() + crit.len();
+        format!(
+            "{} condition{} ok",
+            count,
+            if count == 1 { "" } else { "s" }
+        )
+    } else {
+        problems.sort_by_key(|(level, _)| std::cmp::Reverse(*level));
+        problems
+            .into_iter()
+            .map(|(_, text)| text)
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+
+    let perfdata = perfdata(snapshot, warn, crit);
+    let line = if perfdata.is_empty() {
+        format!("GIM {} - {}", status, summary)
+    } else {
+        format!("GIM {} - {} | {}", status, summary, perfdata)
+    };
+    (status, line)
+}
+
+/// Plugin perfdata, one `'label'=value[uom];warn;crit` entry per referenced metric.
+fn perfdata(snapshot: &MetricsSnapshot, warn: &[Condition], crit: &[Condition]) -> String {
+    let mut paths: Vec<&MetricPath> = Vec::new();
+    for condition in warn.iter().chain(crit) {
+        if !paths.contains(&&condition.path) {
+            paths.push(&condition.path);
+        }
+    }
+
+    paths
+        .iter()
+        .filter_map(|path| {
+            let value = snapshot.get(path).and_then(MetricValue::as_f64)?;
+            let shares_module = paths.iter().filter(|p| p.module == path.module).count() > 1;
+            let label = if shares_module {
+                path.to_string()
+            } else {
+                path.module.clone()
+            };
+
+            let range = |conditions: &[Condition]| {
+                conditions
+                    .iter()
+                    .find(|c| c.path == **path)
+                    .and_then(threshold_range)
+                    .unwrap_or_default()
+            };
+
+            Some(format!(
+                "'{}'={}{};{};{}",
+                label,
+                format_value(value),
+                unit_of(&path.key),
+                range(warn),
+                range(crit)
+            ))
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Expresses a condition in plugin range syntax: `80` alerts above 80, `10:` below 10.
+fn threshold_range(condition: &Condition) -> Option<String> {
+    match condition.op {
+        Comparison::Greater | Comparison::GreaterEqual => Some(format_value(condition.value)),
+        Comparison::Less | Comparison::LessEqual => {
+            Some(format!("{}:", format_value(condition.value)))
+        }
+        Comparison::Equal | Comparison::NotEqual => None,
+    }
+}
+
+fn unit_of(key: &str) -> &'static str {
+    if key.ends_with("_percent") {
+        "%"
+    } else if key.ends_with("_bytes") {
+        "B"
+    } else if key.ends_with("_seconds") {
+        "s"
+    } else {
+        ""
+    }
+}
+
+fn format_value(value: f64) -> String {
+    let text = format!("{:.2}", value);
+    text.trim_end_matches('0').trim_end_matches('.').to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::MetricData;
+    use std::time::SystemTime;
+
+    fn snapshot() -> MetricsSnapshot {
+        let module = |name: &str, metrics: &[(&str, f64)]| {
+            (
+                name.to_string(),
+                MetricData {
+                    timestamp: SystemTime::now(),
+                    metrics: metrics
+                        .iter()
+                        .map(|(k, v)| (k.to_string(), MetricValue::Float(*v)))
+                        .collect(),
+                },
+            )
+        };
+        MetricsSnapshot {
+            modules: vec![
+                module("cpu", &[("cpu_usage_percent", 85.0), ("load_seconds", 1.5)]),
+                module("memory", &[("available_memory_bytes", 512.0)]),
+            ],
+            alerts: Vec::new(),
+            anomalies: Vec::new(),
+        }
+    }
+
+    fn conditions(list: &[&str]) -> Vec<Condition> {
+        let list: Vec<String> = list.iter().map(|c| c.to_string()).collect();
+        parse_conditions(&list).unwrap()
+    }
+
+    #[test]
+    fn reports_the_worst_matching_level() {
+        let warn = conditions(&["cpu.cpu_usage_percent>80"]);
+        let crit = conditions(&["cpu.cpu_usage_percent>=90"]);
+        assert_eq!(
+            evaluate(&snapshot(), &warn, &crit),
+            (
+                CheckStatus::Warning,
+                "GIM WARNING - cpu.cpu_usage_percent=85 (>80) | 'cpu'=85%;80;90".to_string()
+            )
+        );
+
+        let crit = conditions(&["cpu.cpu_usage_percent>=85"]);
+        let (status, line) = evaluate(&snapshot(), &warn, &crit);
+        assert_eq!(status, CheckStatus::Critical);
+        assert!(line.starts_with(
+            "GIM CRITICAL - cpu.cpu_usage_percent=85 (>=85), cpu.cpu_usage_percent=85 (>80) |"
+        ));
+    }
+
+    #[test]
+    fn ok_when_nothing_matches() {
+        let warn = conditions(&[
+            "cpu.cpu_usage_percent>95",
+            "memory.available_memory_bytes<100",
+        ]);
+        assert_eq!(
+            evaluate(&snapshot(), &warn, &[]),
+            (
+                CheckStatus::Ok,
+                "GIM OK - 2 conditions ok | 'cpu'=85%;95; 'memory'=512B;100:;".to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn missing_metrics_are_unknown() {
+        let warn = conditions(&["cpu.cpu_usage_percent>80", "disk.usage_percent>90"]);
+        let (status, line) = evaluate(&snapshot(), &warn, &[]);
+        assert_eq!(status, CheckStatus::Unknown);
+        assert!(line.starts_with(
+            "GIM UNKNOWN - disk.usage_percent not available, cpu.cpu_usage_percent=85 (>80)"
+        ));
+    }
+
+    #[test]
+    fn perfdata_labels_by_path_when_a_module_repeats() {
+        let warn = conditions(&["cpu.cpu_usage_percent>80", "cpu.load_seconds>4"]);
+        assert_eq!(
+            perfdata(&snapshot(), &warn, &[]),
+            "'cpu.cpu_usage_percent'=85%;80; 'cpu.load_seconds'=1.5s;4;"
+        );
+    }
+
+    #[test]
+    fn threshold_ranges_follow_plugin_syntax() {
+        let range = |c: &str| threshold_range(&Condition::parse(c).unwrap());
+        assert_eq!(range("cpu.x>80"), Some("80".to_string()));
+        assert_eq!(range("cpu.x>=80.5"), Some("80.5".to_string()));
+        assert_eq!(range("cpu.x<10"), Some("10:".to_string()));
+        assert_eq!(range("cpu.x<=0.25"), Some("0.25:".to_string()));
+        assert_eq!(range("cpu.x==1"), None);
+        assert_eq!(range("cpu.x!=1"), None);
+    }
+
+    #[test]
+    fn statuses_map_to_plugin_exit_codes() {
+        let codes: Vec<i32> = [
+            CheckStatus::Ok,
+            CheckStatus::Warning,
+            CheckStatus::Critical,
+            CheckStatus::Unknown,
+        ]
+        .iter()
+        .map(CheckStatus::code)
+        .collect();
+        assert_eq!(codes, vec![0, 1, 2, 3]);
+
+        let check = |status| GimError::Check {
+            status,
+            message: String::new(),
+        };
+        assert_eq!(check(CheckStatus::Critical).exit_code(), 2);
+        assert_eq!(check(CheckStatus::Warning).exit_code(), 1);
+
+        // Any other failure in check mode becomes UNKNOWN rather than its usual exit code.
+        let error = report(Err(GimError::UnknownModule("gpu".to_string()))).unwrap_err();
+        assert_eq!(error.exit_code(), 3);
+        assert!(matches!(
+            error,
+            GimError::Check { ref message, .. } if message == "GIM UNKNOWN - unknown module: gpu"
+        ));
+        assert_eq!(
+            report(Err(check(CheckStatus::Warning)))
+                .unwrap_err()
+                .exit_code(),
+            1
+        );
+        assert_eq!(
+            report(Err(unknown("no conditions")))
+                .unwrap_err()
+                .exit_code(),
+            3
+        );
+        assert!(report(Ok((CheckStatus::Ok, "GIM OK - 1 condition ok".to_string()))).is_ok());
+    }
+
+    #[test]
+    fn rejects_malformed_conditions() {
+        let list = vec!["cpu.cpu_usage_percent>80".to_string(), "cpu>80".to_string()];
+        assert!(matches!(
+            parse_conditions(&list),
+            Err(GimError::InvalidCondition(c)) if c == "cpu>80"
+        ));
+    }
+}
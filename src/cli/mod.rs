@@ -35,6 +35,37 @@ pub enum Command {
         watch: bool,
     },
 
+    #[command(about = "Stream status bar JSON for i3bar/swaybar or waybar")]
+    Bar {
+        #[arg(short, long, value_enum, help = "Bar protocol")]
+        protocol: Option<BarProtocolArg>,
+
+        #[arg(
+            short = 'M',
+            long,
+            value_delimiter = ',',
+            help = "Metrics to show (module.metric,...)"
+        )]
+        metric: Option<Vec<String>>,
+    },
+
+    #[command(about = "Run as a Nagios/Icinga check plugin (exit 0/1/2/3)")]
+    Check {
+        #[arg(
+            long,
+            value_delimiter = ',',
+            help = "Warning condition, e.g. 'cpu.cpu_usage_percent>80'"
+        )]
+        warn: Vec<String>,
+
+        #[arg(
+            long,
+            value_delimiter = ',',
+            help = "Critical condition, e.g. 'disk.usage_percent>95'"
+        )]
+        crit: Vec<String>,
+    },
+
     #[command(about = "Launch interactive TUI dashboard")]
     Tui {
         #[arg(short, long, value_delimiter = ',', help = "Modules to display (cpu,memory,disk)")]
@@ -49,6 +80,44 @@ pub enum OutputFormatArg {
     Raw,
 }
 
+#[derive(Clone, ValueEnum)]
+pub enum BarProtocolArg {
+    I3bar,
+    Waybar,
+}
+
 pub fn parse_args() -> Cli {
     Cli::parse()
 }
+
+/// Whether the arguments ask for `gim check`, judged from the raw arguments so it still
+/// works when they fail to parse. Global options may come before the subcommand.
+pub fn is_check_invocation<I: IntoIterator<Item = String>>(args: I) -> bool {
+    let mut args = args.into_iter().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-c" | "--config" => {
+                args.next();
+            }
+            flag if flag.starts_with('-') => {}
+            subcommand => return subcommand == "check",
+        }
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_check_subcommand_before_parsing() {
+        let check = |line: &str| is_check_invocation(line.split_whitespace().map(String::from));
+        assert!(check("gim check --bogus"));
+        assert!(check("gim -c check.yaml --no-config check"));
+        assert!(check("gim --config=x.yaml check --warn"));
+        assert!(!check("gim -c check print"));
+        assert!(!check("gim print -m check"));
+        assert!(!check("gim --bogus"));
+    }
+}
@@ -1,16 +1,24 @@
+use crate::core::Severity;
 use crate::error::{GimError, Result};
 use directories::ProjectDirs;
 use ratatui::style::Color;
 use serde::Deserialize;
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, Clone, Default)]
 #[serde(default)]
 pub struct Config {
     pub general: GeneralConfig,
     pub print: PrintConfig,
     pub tui: TuiConfig,
     pub theme: ThemeConfig,
+    pub modules: ModulesConfig,
+    pub bar: BarConfig,
+    pub derived: Vec<String>,
+    pub alerts: AlertsConfig,
+    pub anomaly: AnomalyConfig,
+    pub sinks: SinksConfig,
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -26,6 +34,25 @@ pub struct PrintConfig {
     pub output: String,
     pub show_units: bool,
     pub watch: bool,
+    pub color: ColorMode,
+    pub thresholds: HashMap<String, Threshold>,
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ColorMode {
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+/// Warn/critical levels for a metric. When `critical` is below `warning` the metric is
+/// treated as lower-is-worse (e.g. free space).
+#[derive(Debug, Deserialize, Clone, Copy)]
+pub struct Threshold {
+    pub warning: f64,
+    pub critical: f64,
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -36,11 +63,87 @@ pub struct TuiConfig {
     pub show_help: bool,
 }
 
+#[derive(Debug, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct ModulesConfig {
+    pub disk: DiskConfig,
+    pub network: NetworkConfig,
+    pub sensors: SensorsConfig,
+    pub cgroup: CgroupConfig,
+    pub cgroups: CgroupsConfig,
+}
+
+/// Mount and device filters for the disk module. Empty include lists mean
+/// "everything"; mount and device patterns may use `*`.
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct DiskConfig {
+    pub include_fs_types: Vec<String>,
+    pub exclude_fs_types: Vec<String>,
+    pub include_mounts: Vec<String>,
+    pub exclude_mounts: Vec<String>,
+    pub io_include_partitions: bool,
+    pub io_exclude_devices: Vec<String>,
+}
+
+/// Interface filters for the network module. Virtual interfaces are those without a
+/// backing device (bridges, veths, tunnels); name patterns may use `*`.
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct NetworkConfig {
+    pub include_loopback: bool,
+    pub include_virtual: bool,
+    pub include_interfaces: Vec<String>,
+    pub exclude_interfaces: Vec<String>,
+}
+
+/// Temperatures within `warning_margin_celsius` of a sensor's critical point are
+/// highlighted in the TUI.
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct SensorsConfig {
+    pub warning_margin_celsius: f64,
+}
+
+/// With `relative_to_limits`, the cpu and memory modules report usage against the
+/// current cgroup's `cpu.max` and `memory.max` instead of the host's totals.
+#[derive(Debug, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct CgroupConfig {
+    pub relative_to_limits: bool,
+}
+
+/// How far below the cgroup root the `cgroups` module looks (1 = direct children
+/// such as `system.slice`) and how many cgroups each ranking lists.
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct CgroupsConfig {
+    pub depth: usize,
+    pub top_n: usize,
+}
+
 #[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct BarConfig {
+    pub protocol: BarProtocol,
+    pub refresh_ms: Option<u64>,
+    pub metrics: Vec<String>,
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum BarProtocol {
+    #[default]
+    I3bar,
+    Waybar,
+}
+
+#[derive(Debug, Deserialize, Clone, Default)]
 #[serde(rename_all = "snake_case")]
 pub enum BorderStyle {
     None,
     Plain,
+    #[default]
     Rounded,
 }
 
@@ -53,6 +156,13 @@ pub struct ThemeConfig {
     pub network: ModuleTheme,
     pub process: ModuleTheme,
     pub system: ModuleTheme,
+    pub sockets: ModuleTheme,
+    pub sensors: ModuleTheme,
+    pub power: ModuleTheme,
+    pub pressure: ModuleTheme,
+    pub cgroup: ModuleTheme,
+    pub cgroups: ModuleTheme,
+    pub derived: ModuleTheme,
     pub chrome: ChromeTheme,
 }
 
@@ -70,20 +180,176 @@ pub struct ChromeTheme {
     pub border: String,
     pub title: String,
     pub header: String,
+    pub warning: String,
     pub error: String,
 }
 
-impl Default for Config {
+impl Default for DiskConfig {
     fn default() -> Self {
         Self {
-            general: GeneralConfig::default(),
-            print: PrintConfig::default(),
-            tui: TuiConfig::default(),
-            theme: ThemeConfig::default(),
+            include_fs_types: Vec::new(),
+            exclude_fs_types: vec![
+                "tmpfs".into(),
+                "devtmpfs".into(),
+                "overlay".into(),
+                "squashfs".into(),
+                "ramfs".into(),
+            ],
+            include_mounts: Vec::new(),
+            exclude_mounts: Vec::new(),
+            io_include_partitions: false,
+            io_exclude_devices: vec!["loop*".into(), "ram*".into()],
         }
     }
 }
 
+impl Default for NetworkConfig {
+    fn default() -> Self {
+        Self {
+            include_loopback: false,
+            include_virtual: true,
+            include_interfaces: Vec::new(),
+            exclude_interfaces: Vec::new(),
+        }
+    }
+}
+
+impl Default for SensorsConfig {
+    fn default() -> Self {
+        Self {
+            warning_margin_celsius: 10.0,
+        }
+    }
+}
+
+impl Default for CgroupsConfig {
+    fn default() -> Self {
+        Self {
+            depth: 2,
+            top_n: 10,
+        }
+    }
+}
+
+impl Default for BarConfig {
+    fn default() -> Self {
+        Self {
+            protocol: BarProtocol::I3bar,
+            refresh_ms: None,
+            metrics: vec![
+                "cpu.cpu_usage_percent".into(),
+                "memory.memory_usage_percent".into(),
+                "disk.usage_percent".into(),
+            ],
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct AlertsConfig {
+    pub hysteresis_percent: f64,
+    pub rules: Vec<String>,
+}
+
+impl Default for AlertsConfig {
+    fn default() -> Self {
+        Self {
+            hysteresis_percent: 5.0,
+            rules: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct AnomalyConfig {
+    pub metrics: Vec<String>,
+    pub method: AnomalyMethod,
+    pub threshold: f64,
+    pub alpha: f64,
+    pub window: usize,
+    pub warmup: usize,
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AnomalyMethod {
+    #[default]
+    Ewma,
+    Mad,
+}
+
+impl Default for AnomalyConfig {
+    fn default() -> Self {
+        Self {
+            metrics: Vec::new(),
+            method: AnomalyMethod::Ewma,
+            threshold: 3.0,
+            alpha: 0.1,
+            window: 60,
+            warmup: 10,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct SinksConfig {
+    pub statsd: Option<StatsdConfig>,
+    pub otlp: Option<OtlpConfig>,
+    pub graphite: Option<GraphiteConfig>,
+    pub webhook: Option<WebhookConfig>,
+    pub exec: Option<ExecHookConfig>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct StatsdConfig {
+    pub host: String,
+    pub port: u16,
+    pub prefix: String,
+    pub dogstatsd_tags: bool,
+    pub max_packet_bytes: usize,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct OtlpConfig {
+    pub endpoint: String,
+    pub prefix: String,
+    pub headers: HashMap<String, String>,
+    pub timeout_ms: u64,
+    pub max_retries: u32,
+    pub backoff_ms: u64,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct GraphiteConfig {
+    pub host: String,
+    pub port: u16,
+    pub prefix: String,
+    pub timeout_ms: u64,
+    pub max_buffered_points: usize,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct WebhookConfig {
+    pub url: String,
+    pub headers: HashMap<String, String>,
+    pub timeout_ms: u64,
+    pub cooldown_secs: u64,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct ExecHookConfig {
+    pub command: String,
+    pub cooldown_secs: u64,
+}
+
 impl Default for GeneralConfig {
     fn default() -> Self {
         Self {
@@ -102,10 +368,31 @@ impl Default for GeneralConfig {
 
 impl Default for PrintConfig {
     fn default() -> Self {
+        let thresholds = [
+            "cpu.cpu_usage_percent",
+            "memory.memory_usage_percent",
+            "disk.usage_percent",
+            "disk.mount.*.usage_percent",
+            "disk.mount.*.inode_usage_percent",
+        ]
+        .into_iter()
+        .map(|key| {
+            (
+                key.to_string(),
+                Threshold {
+                    warning: 75.0,
+                    critical: 90.0,
+                },
+            )
+        })
+        .collect();
+
         Self {
             output: "table".into(),
             show_units: true,
             watch: false,
+            color: ColorMode::Auto,
+            thresholds,
         }
     }
 }
@@ -120,12 +407,6 @@ impl Default for TuiConfig {
     }
 }
 
-impl Default for BorderStyle {
-    fn default() -> Self {
-        BorderStyle::Rounded
-    }
-}
-
 impl Default for ThemeConfig {
     fn default() -> Self {
         Self {
@@ -159,6 +440,41 @@ impl Default for ThemeConfig {
                 fg: "blue".into(),
                 accent: "light_blue".into(),
             },
+            sockets: ModuleTheme {
+                label: "Sockets".into(),
+                fg: "light_cyan".into(),
+                accent: "cyan".into(),
+            },
+            sensors: ModuleTheme {
+                label: "Sensors".into(),
+                fg: "light_red".into(),
+                accent: "red".into(),
+            },
+            power: ModuleTheme {
+                label: "Power".into(),
+                fg: "light_green".into(),
+                accent: "green".into(),
+            },
+            pressure: ModuleTheme {
+                label: "Pressure".into(),
+                fg: "light_yellow".into(),
+                accent: "yellow".into(),
+            },
+            cgroup: ModuleTheme {
+                label: "Cgroup".into(),
+                fg: "light_blue".into(),
+                accent: "blue".into(),
+            },
+            cgroups: ModuleTheme {
+                label: "Cgroups".into(),
+                fg: "light_magenta".into(),
+                accent: "magenta".into(),
+            },
+            derived: ModuleTheme {
+                label: "Derived".into(),
+                fg: "white".into(),
+                accent: "gray".into(),
+            },
             chrome: ChromeTheme::default(),
         }
     }
@@ -180,15 +496,136 @@ impl Default for ChromeTheme {
             border: "gray".into(),
             title: "white".into(),
             header: "white".into(),
+            warning: "yellow".into(),
             error: "red".into(),
         }
     }
 }
 
+impl Default for StatsdConfig {
+    fn default() -> Self {
+        Self {
+            host: "127.0.0.1".into(),
+            port: 8125,
+            prefix: "gim".into(),
+            dogstatsd_tags: false,
+            max_packet_bytes: 1432,
+        }
+    }
+}
+
+impl Default for OtlpConfig {
+    fn default() -> Self {
+        Self {
+            endpoint: "http://localhost:4318/v1/metrics".into(),
+            prefix: "gim".into(),
+            headers: HashMap::new(),
+            timeout_ms: 5000,
+            max_retries: 3,
+            backoff_ms: 500,
+        }
+    }
+}
+
+impl Default for GraphiteConfig {
+    fn default() -> Self {
+        Self {
+            host: "127.0.0.1".into(),
+            port: 2003,
+            prefix: "gim".into(),
+            timeout_ms: 2000,
+            max_buffered_points: 10_000,
+        }
+    }
+}
+
+impl Threshold {
+    pub fn severity(&self, value: f64) -> Severity {
+        let lower_is_worse = self.critical < self.warning;
+        let crosses = |limit: f64| {
+            if lower_is_worse {
+                value <= limit
+            } else {
+                value >= limit
+            }
+        };
+
+        if crosses(self.critical) {
+            Severity::Critical
+        } else if crosses(self.warning) {
+            Severity::Warning
+        } else {
+            Severity::Ok
+        }
+    }
+}
+
+impl PrintConfig {
+    /// Looks up the threshold for `module.key`. Exact keys win; otherwise the longest
+    /// matching `*` pattern is used, e.g. `disk.*_percent`.
+    pub fn threshold_for(&self, module: &str, key: &str) -> Option<&Threshold> {
+        let full = format!("{}.{}", module, key);
+        if let Some(threshold) = self.thresholds.get(&full) {
+            return Some(threshold);
+        }
+
+        self.thresholds
+            .iter()
+            .filter(|(pattern, _)| pattern.contains('*') && glob_match(pattern, &full))
+            .max_by_key(|(pattern, _)| pattern.len())
+            .map(|(_, threshold)| threshold)
+    }
+}
+
+impl ThemeConfig {
+    pub fn module(&self, name: &str) -> &ModuleTheme {
+        match name {
+            "cpu" => &self.cpu,
+            "memory" => &self.memory,
+            "disk" => &self.disk,
+            "network" => &self.network,
+            "process" => &self.process,
+            "system" => &self.system,
+            "sockets" => &self.sockets,
+            "sensors" => &self.sensors,
+            "power" => &self.power,
+            "pressure" => &self.pressure,
+            "cgroup" => &self.cgroup,
+            "cgroups" => &self.cgroups,
+            "derived" => &self.derived,
+            _ => &self.cpu,
+        }
+    }
+}
+
+impl Default for WebhookConfig {
+    fn default() -> Self {
+        Self {
+            url: "http://localhost:9000/alerts".into(),
+            headers: HashMap::new(),
+            timeout_ms: 5000,
+            cooldown_secs: 300,
+        }
+    }
+}
+
+impl Default for ExecHookConfig {
+    fn default() -> Self {
+        Self {
+            command: String::new(),
+            cooldown_secs: 300,
+        }
+    }
+}
+
 impl Config {
     pub fn tui_refresh_ms(&self) -> u64 {
         self.tui.refresh_ms.unwrap_or(self.general.refresh_ms)
     }
+
+    pub fn bar_refresh_ms(&self) -> u64 {
+        self.bar.refresh_ms.unwrap_or(self.general.refresh_ms)
+    }
 }
 
 pub fn default_config_path() -> Option<PathBuf> {
@@ -253,3 +690,63 @@ pub fn parse_color(color_str: &str) -> Color {
         _ => Color::White,
     }
 }
+
+/// Matches `text` against a pattern where `*` stands for any run of characters.
+pub fn glob_match(pattern: &str, text: &str) -> bool {
+    let parts: Vec<&str> = pattern.split('*').collect();
+    if parts.len() == 1 {
+        return pattern == text;
+    }
+
+    let (first, last) = (parts[0], parts[parts.len() - 1]);
+    // Stripping (rather than slicing by length) keeps multi-byte characters intact and
+    // stops the prefix and suffix from overlapping.
+    let Some(mut rest) = text
+        .strip_prefix(first)
+        .and_then(|rest| rest.strip_suffix(last))
+    else {
+        return false;
+    };
+    for part in &parts[1..parts.len() - 1] {
+        match rest.find(part) {
+            Some(idx) => rest = &rest[idx + part.len()..],
+            None => return false,
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn globs_match_prefixes_suffixes_and_middles() {
+        assert!(glob_match(
+            "disk.mount.*.usage_percent",
+            "disk.mount./home.usage_percent"
+        ));
+        assert!(glob_match("veth*", "veth12ab"));
+        assert!(glob_match("*", ""));
+        assert!(glob_match("a*b*c", "a-b-c"));
+        assert!(!glob_match("a*b*c", "a-c-b"));
+        assert!(!glob_match("ab*b", "ab"));
+        assert!(glob_match("cpu", "cpu"));
+        assert!(!glob_match("cpu", "cpu2"));
+    }
+
+    #[test]
+    fn globs_handle_non_ascii_text() {
+        assert!(!glob_match("*x", "é"));
+        assert!(!glob_match("x*", "é"));
+        assert!(glob_match("*é", "café"));
+        assert!(glob_match(
+            "disk.mount.*.usage_percent",
+            "disk.mount./media/Données.usage_percent"
+        ));
+        assert!(!glob_match(
+            "disk.mount.*.inode_usage_percent",
+            "disk.mount./médiä"
+        ));
+    }
+}
@@ -3,6 +3,13 @@ use std::collections::HashMap;
 pub trait MetricCollector {
     fn collect(&self) -> Result<MetricData, Box<dyn std::error::Error>>;
     fn name(&self) -> &'static str;
+
+    /// Takes the first reading of any counters reported as rates, ahead of the first
+    /// `collect`. Returns whether it did, in which case the caller should let some time
+    /// pass before collecting.
+    fn prime(&self) -> bool {
+        false
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -49,3 +56,218 @@ impl From<bool> for MetricValue {
         MetricValue::Boolean(value)
     }
 }
+
+impl MetricValue {
+    /// Numeric view of the value, if it has one. Booleans map to 0/1.
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            MetricValue::Integer(i) => Some(*i as f64),
+            MetricValue::Float(f) => Some(*f),
+            MetricValue::Boolean(b) => Some(if *b { 1.0 } else { 0.0 }),
+            MetricValue::String(_) | MetricValue::List(_) => None,
+        }
+    }
+}
+
+/// Whether a metric is a point-in-time reading or a monotonically increasing total.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MetricKind {
+    Gauge,
+    Counter,
+}
+
+impl MetricKind {
+    /// Counters are named with a `_total` suffix; the network byte totals predate that
+    /// convention and are matched explicitly.
+    pub fn of(key: &str) -> Self {
+        if key.ends_with("_total")
+            || key == "total_received_bytes"
+            || key == "total_transmitted_bytes"
+        {
+            MetricKind::Counter
+        } else {
+            MetricKind::Gauge
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Ok,
+    Warning,
+    Critical,
+}
+
+/// A `module.metric` reference, e.g. `cpu.cpu_usage_percent`. Only the first dot
+/// separates the module, so metric keys may themselves contain dots.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct MetricPath {
+    pub module: String,
+    pub key: String,
+}
+
+impl MetricPath {
+    pub fn parse(s: &str) -> Option<Self> {
+        let (module, key) = s.trim().split_once('.')?;
+        if module.is_empty() || key.is_empty() {
+            return None;
+        }
+        Some(Self {
+            module: module.to_string(),
+            key: key.to_string(),
+        })
+    }
+}
+
+impl std::fmt::Display for MetricPath {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{}", self.module, self.key)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Comparison {
+    Greater,
+    GreaterEqual,
+    Less,
+    LessEqual,
+    Equal,
+    NotEqual,
+}
+
+impl Comparison {
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            Comparison::Greater => ">",
+            Comparison::GreaterEqual => ">=",
+            Comparison::Less => "<",
+            Comparison::LessEqual => "<=",
+            Comparison::Equal => "==",
+            Comparison::NotEqual => "!=",
+        }
+    }
+
+    pub fn holds(&self, lhs: f64, rhs: f64) -> bool {
+        match self {
+            Comparison::Greater => lhs > rhs,
+            Comparison::GreaterEqual => lhs >= rhs,
+            Comparison::Less => lhs < rhs,
+            Comparison::LessEqual => lhs <= rhs,
+            Comparison::Equal => lhs == rhs,
+            Comparison::NotEqual => lhs != rhs,
+        }
+    }
+}
+
+/// A comparison of one metric against a constant, written `cpu.cpu_usage_percent>80`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Condition {
+    pub path: MetricPath,
+    pub op: Comparison,
+    pub value: f64,
+}
+
+impl Condition {
+    pub fn parse(s: &str) -> Option<Self> {
+        // Two-character operators first so `>=` is not read as `>` followed by `=80`.
+        const OPERATORS: [(&str, Comparison); 6] = [
+            (">=", Comparison::GreaterEqual),
+            ("<=", Comparison::LessEqual),
+            ("==", Comparison::Equal),
+            ("!=", Comparison::NotEqual),
+            (">", Comparison::Greater),
+            ("<", Comparison::Less),
+        ];
+
+        let (idx, symbol, op) = OPERATORS
+            .iter()
+            .filter_map(|(symbol, op)| s.find(symbol).map(|idx| (idx, *symbol, *op)))
+            .min_by_key(|(idx, symbol, _)| (*idx, std::cmp::Reverse(symbol.len())))?;
+
+        let path = MetricPath::parse(&s[..idx])?;
+        let value = s[idx + symbol.len()..].trim().parse().ok()?;
+        Some(Self { path, op, value })
+    }
+
+    pub fn holds(&self, value: f64) -> bool {
+        self.op.holds(value, self.value)
+    }
+}
+
+impl std::fmt::Display for Condition {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}{}{}", self.path, self.op.symbol(), self.value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_conditions() {
+        let parse = |s: &str| {
+            let c = Condition::parse(s).unwrap();
+            (c.path.to_string(), c.op, c.value)
+        };
+        assert_eq!(
+            parse("cpu.cpu_usage_percent>80"),
+            (
+                "cpu.cpu_usage_percent".to_string(),
+                Comparison::Greater,
+                80.0
+            )
+        );
+        assert_eq!(
+            parse("cpu.cpu_usage_percent>=80"),
+            (
+                "cpu.cpu_usage_percent".to_string(),
+                Comparison::GreaterEqual,
+                80.0
+            )
+        );
+        assert_eq!(
+            parse(" memory.available_memory_bytes <= 1e9 "),
+            (
+                "memory.available_memory_bytes".to_string(),
+                Comparison::LessEqual,
+                1e9
+            )
+        );
+        assert_eq!(
+            parse("disk.mount./.usage_percent<5").0,
+            "disk.mount./.usage_percent"
+        );
+        assert_eq!(parse("system.up==1").1, Comparison::Equal);
+        assert_eq!(parse("system.up!=-1.5").2, -1.5);
+    }
+
+    #[test]
+    fn rejects_malformed_conditions() {
+        for bad in ["cpu>80", ".x>1", "cpu.x>", "cpu.x>eighty", "cpu.x"] {
+            assert_eq!(Condition::parse(bad), None, "{}", bad);
+        }
+    }
+
+    #[test]
+    fn conditions_compare_against_their_value() {
+        let c = Condition::parse("cpu.x>=80").unwrap();
+        assert!(c.holds(80.0) && c.holds(95.0) && !c.holds(79.9));
+        assert_eq!(c.to_string(), "cpu.x>=80");
+    }
+
+    #[test]
+    fn metric_paths_split_on_the_first_dot() {
+        let path = MetricPath::parse("network.interface.eth0.rx_bytes").unwrap();
+        assert_eq!(path.module, "network");
+        assert_eq!(path.key, "interface.eth0.rx_bytes");
+        assert_eq!(MetricPath::parse("network"), None);
+    }
+
+    #[test]
+    fn counters_are_recognised_by_name() {
+        assert_eq!(MetricKind::of("interrupts_total"), MetricKind::Counter);
+        assert_eq!(MetricKind::of("total_received_bytes"), MetricKind::Counter);
+        assert_eq!(MetricKind::of("total_memory_bytes"), MetricKind::Gauge);
+    }
+}
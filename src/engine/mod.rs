@@ -1,40 +1,119 @@
-use crate::core::{MetricCollector, MetricData};
+use std::collections::HashMap;
+
+use crate::alerts::{Alert, AlertEvaluator};
+use crate::anomaly::{Anomaly, AnomalyDetector};
+use crate::config::Config;
+use crate::core::{MetricCollector, MetricData, MetricPath, MetricValue};
 use crate::error::{GimError, Result};
+use crate::expr::DerivedMetric;
+use crate::modules::cgroup::CgroupCollector;
+use crate::modules::cgroups::CgroupsCollector;
 use crate::modules::cpu::CpuCollector;
+use crate::modules::delta::INITIAL_INTERVAL;
 use crate::modules::disk::DiskCollector;
 use crate::modules::memory::MemoryCollector;
 use crate::modules::network::NetworkCollector;
+use crate::modules::power::PowerCollector;
+use crate::modules::pressure::PressureCollector;
 use crate::modules::process::ProcessCollector;
+use crate::modules::sensors::SensorsCollector;
+use crate::modules::sockets::SocketsCollector;
 use crate::modules::system::SystemCollector;
 
 pub struct MetricsSnapshot {
     pub modules: Vec<(String, MetricData)>,
+    pub alerts: Vec<Alert>,
+    pub anomalies: Vec<Anomaly>,
+}
+
+impl MetricsSnapshot {
+    pub fn get(&self, path: &MetricPath) -> Option<&MetricValue> {
+        self.modules
+            .iter()
+            .find(|(name, _)| *name == path.module)
+            .and_then(|(_, data)| data.metrics.get(&path.key))
+    }
 }
 
 pub struct Engine {
     collectors: Vec<Box<dyn MetricCollector>>,
+    derived: Vec<DerivedMetric>,
+    anomalies: AnomalyDetector,
+    alerts: AlertEvaluator,
+    primed: bool,
 }
 
 impl Engine {
-    pub fn new(module_names: &[String]) -> Result<Self> {
+    pub fn new(module_names: &[String], config: &Config) -> Result<Self> {
+        let derived = config
+            .derived
+            .iter()
+            .map(|definition| {
+                DerivedMetric::parse(definition).map_err(|reason| GimError::InvalidDerived {
+                    definition: definition.clone(),
+                    reason,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        // Asking for `derived` alone should still collect whatever the expressions read.
+        let mut module_names = module_names.to_vec();
+        if module_names.iter().any(|name| name == "derived") {
+            for metric in &derived {
+                for path in metric.expr.metric_paths() {
+                    if path.module != "derived" && !module_names.contains(&path.module) {
+                        module_names.push(path.module.clone());
+                    }
+                }
+            }
+        }
+
         let mut collectors: Vec<Box<dyn MetricCollector>> = Vec::new();
 
-        for name in module_names {
+        for name in &module_names {
             match name.as_str() {
-                "cpu" => collectors.push(Box::new(CpuCollector::new())),
-                "memory" => collectors.push(Box::new(MemoryCollector::new())),
-                "disk" => collectors.push(Box::new(DiskCollector::new())),
-                "network" => collectors.push(Box::new(NetworkCollector::new())),
+                "cpu" => collectors.push(Box::new(CpuCollector::with_cgroup_limits(
+                    config.modules.cgroup.relative_to_limits,
+                ))),
+                "memory" => collectors.push(Box::new(MemoryCollector::with_cgroup_limits(
+                    config.modules.cgroup.relative_to_limits,
+                ))),
+                "disk" => collectors.push(Box::new(DiskCollector::with_config(
+                    config.modules.disk.clone(),
+                ))),
+                "network" => collectors.push(Box::new(NetworkCollector::with_config(
+                    config.modules.network.clone(),
+                ))),
                 "process" => collectors.push(Box::new(ProcessCollector::new())),
                 "system" => collectors.push(Box::new(SystemCollector::new())),
+                "sockets" => collectors.push(Box::new(SocketsCollector::new())),
+                "sensors" => collectors.push(Box::new(SensorsCollector::new())),
+                "power" => collectors.push(Box::new(PowerCollector::new())),
+                "pressure" => collectors.push(Box::new(PressureCollector::new())),
+                "cgroup" => collectors.push(Box::new(CgroupCollector::new())),
+                "cgroups" => collectors.push(Box::new(CgroupsCollector::with_config(
+                    config.modules.cgroups.clone(),
+                ))),
+                // Published by the engine itself whenever derived metrics are configured.
+                "derived" => {}
                 other => return Err(GimError::UnknownModule(other.to_string())),
             }
         }
 
-        Ok(Self { collectors })
+        Ok(Self {
+            collectors,
+            derived,
+            anomalies: AnomalyDetector::new(config.anomaly.clone()),
+            alerts: AlertEvaluator::from_config(&config.alerts)?,
+            primed: false,
+        })
     }
 
-    pub fn collect_once(&self) -> MetricsSnapshot {
+    pub fn collect_once(&mut self) -> MetricsSnapshot {
+        if !self.primed {
+            self.prime();
+        }
+
         let mut modules = Vec::new();
 
         for collector in &self.collectors {
@@ -48,7 +127,55 @@ impl Engine {
             }
         }
 
-        MetricsSnapshot { modules }
+        let mut snapshot = MetricsSnapshot {
+            modules,
+            alerts: Vec::new(),
+            anomalies: Vec::new(),
+        };
+        self.evaluate_derived(&mut snapshot);
+        snapshot.anomalies = self.anomalies.evaluate(&snapshot);
+        snapshot.alerts = self.alerts.evaluate(&snapshot);
+        snapshot
+    }
+
+    /// Takes every collector's first counter readings together and waits once, rather
+    /// than each collector that reports rates sleeping through its own first sample.
+    fn prime(&mut self) {
+        self.primed = true;
+        let waiting = self.collectors.iter().filter(|c| c.prime()).count();
+        if waiting > 0 {
+            std::thread::sleep(INITIAL_INTERVAL);
+        }
+    }
+
+    /// Publishes derived metrics as a `derived` module. Definitions are evaluated in
+    /// order, so later ones may refer to earlier ones as `derived.<name>`.
+    fn evaluate_derived(&self, snapshot: &mut MetricsSnapshot) {
+        if self.derived.is_empty() {
+            return;
+        }
+
+        snapshot.modules.push((
+            "derived".to_string(),
+            MetricData {
+                timestamp: std::time::SystemTime::now(),
+                metrics: HashMap::new(),
+            },
+        ));
+        let index = snapshot.modules.len() - 1;
+
+        for metric in &self.derived {
+            if let Some(value) = metric.expr.eval(snapshot) {
+                snapshot.modules[index]
+                    .1
+                    .metrics
+                    .insert(metric.name.clone(), MetricValue::Float(value));
+            }
+        }
+
+        if snapshot.modules[index].1.metrics.is_empty() {
+            snapshot.modules.pop();
+        }
     }
 
     pub fn module_names(&self) -> Vec<&str> {
@@ -56,4 +183,7 @@ impl Engine {
     }
 }
 
-pub const AVAILABLE_MODULES: &[&str] = &["cpu", "memory", "disk", "network", "process", "system"];
+pub const AVAILABLE_MODULES: &[&str] = &[
+    "cpu", "memory", "disk", "network", "process", "system", "sockets", "sensors", "power",
+    "pressure", "cgroup", "cgroups",
+];
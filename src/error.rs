@@ -1,5 +1,7 @@
 use thiserror::Error;
 
+use crate::check::CheckStatus;
+
 pub type Result<T> = std::result::Result<T, GimError>;
 
 #[derive(Error, Debug)]
@@ -36,17 +38,51 @@ pub enum GimError {
 
     #[error("unknown output format: {0}")]
     UnknownFormat(String),
+
+    #[error("invalid metric selector: {0} (expected module.metric)")]
+    InvalidMetric(String),
+
+    #[error(
+        "invalid condition: {0} (expected module.metric<op>value, e.g. cpu.cpu_usage_percent>80)"
+    )]
+    InvalidCondition(String),
+
+    #[error("invalid alert rule: {0} (expected e.g. memory.memory_usage_percent > 90 for 2m => critical)")]
+    InvalidRule(String),
+
+    #[error("invalid derived metric '{definition}': {reason}")]
+    InvalidDerived { definition: String, reason: String },
+
+    #[error("check {status}: {message}")]
+    Check {
+        status: CheckStatus,
+        message: String,
+    },
+
+    #[error("{sink} sink error: {source}")]
+    Sink {
+        sink: String,
+        source: Box<dyn std::error::Error + Send + Sync>,
+    },
 }
 
 impl GimError {
     pub fn exit_code(&self) -> i32 {
         match self {
             GimError::Collector { .. } => 2,
-            GimError::ConfigLoad { .. } | GimError::ConfigParse { .. } => 3,
+            GimError::ConfigLoad { .. }
+            | GimError::ConfigParse { .. }
+            | GimError::InvalidRule(_)
+            | GimError::InvalidDerived { .. } => 3,
             GimError::Io(_) => 4,
             GimError::Output(_) => 5,
             GimError::Tui(_) => 6,
-            GimError::UnknownModule(_) | GimError::UnknownFormat(_) => 1,
+            GimError::UnknownModule(_)
+            | GimError::UnknownFormat(_)
+            | GimError::InvalidMetric(_)
+            | GimError::InvalidCondition(_) => 1,
+            GimError::Check { status, .. } => status.code(),
+            GimError::Sink { .. } => 7,
         }
     }
 }
@@ -0,0 +1,420 @@
+use crate::core::{MetricPath, MetricValue};
+use crate::engine::MetricsSnapshot;
+
+/// Arithmetic over snapshot values: `+ - * /`, parentheses, numbers, `module.metric`
+/// references and `min`, `max`, `abs`. Paths containing characters outside
+/// `[A-Za-z0-9_.]` can be written in braces, e.g. `{disk.mount./var.usage_percent}`.
+#[derive(Debug, Clone)]
+pub enum Expr {
+    Number(f64),
+    Metric(MetricPath),
+    Neg(Box<Expr>),
+    Binary(Box<Expr>, BinaryOp, Box<Expr>),
+    Call(Function, Vec<Expr>),
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum BinaryOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum Function {
+    Min,
+    Max,
+    Abs,
+}
+
+impl Expr {
+    pub fn parse(input: &str) -> std::result::Result<Self, String> {
+        let tokens = tokenize(input)?;
+        let mut parser = Parser { tokens, pos: 0 };
+        let expr = parser.expr()?;
+        match parser.peek() {
+            None => Ok(expr),
+            Some(token) => Err(format!("unexpected {:?}", token)),
+        }
+    }
+
+    /// Every metric the expression refers to.
+    pub fn metric_paths(&self) -> Vec<&MetricPath> {
+        match self {
+            Expr::Number(_) => Vec::new(),
+            Expr::Metric(path) => vec![path],
+            Expr::Neg(inner) => inner.metric_paths(),
+            Expr::Binary(lhs, _, rhs) => {
+                let mut paths = lhs.metric_paths();
+                paths.extend(rhs.metric_paths());
+                paths
+            }
+            Expr::Call(_, args) => args.iter().flat_map(Expr::metric_paths).collect(),
+        }
+    }
+
+    /// Evaluates against a snapshot. A missing or non-numeric metric, or a division by
+    /// zero, yields `None` rather than a misleading number.
+    pub fn eval(&self, snapshot: &MetricsSnapshot) -> Option<f64> {
+        let value = match self {
+            Expr::Number(n) => *n,
+            Expr::Metric(path) => snapshot.get(path).and_then(MetricValue::as_f64)?,
+            Expr::Neg(inner) => -inner.eval(snapshot)?,
+            Expr::Binary(lhs, op, rhs) => {
+                let (a, b) = (lhs.eval(snapshot)?, rhs.eval(snapshot)?);
+                match op {
+                    BinaryOp::Add => a + b,
+                    BinaryOp::Sub => a - b,
+                    BinaryOp::Mul => a * b,
+                    BinaryOp::Div if b == 0.0 => return None,
+                    BinaryOp::Div => a / b,
+                }
+            }
+            Expr::Call(function, args) => {
+                let values = args
+                    .iter()
+                    .map(|arg| arg.eval(snapshot))
+                    .collect::<Option<Vec<f64>>>()?;
+                match function {
+                    Function::Min => values.into_iter().reduce(f64::min)?,
+                    Function::Max => values.into_iter().reduce(f64::max)?,
+                    Function::Abs => values.first()?.abs(),
+                }
+            }
+        };
+        value.is_finite().then_some(value)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(f64),
+    Ident(String),
+    Path(String),
+    Op(char),
+    LParen,
+    RParen,
+    Comma,
+}
+
+fn tokenize(input: &str) -> std::result::Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = input.char_indices().peekable();
+
+    while let Some(&(start, c)) = chars.peek() {
+        match c {
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            '0'..='9' | '.' => {
+                let end = number_end(input, start);
+                while chars.next_if(|&(i, _)| i < end).is_some() {}
+                let text = &input[start..end];
+                let number = text
+                    .parse()
+                    .map_err(|_| format!("invalid number {}", text))?;
+                tokens.push(Token::Number(number));
+            }
+            c if c.is_ascii_alphabetic() || c == '_' => {
+                let mut end = start;
+                while let Some(&(i, c)) = chars.peek() {
+                    if c.is_ascii_alphanumeric() || c == '_' || c == '.' {
+                        end = i + c.len_utf8();
+                        chars.next();
+                    } else {
+                        break;
+                    }
+                }
+                tokens.push(Token::Ident(input[start..end].to_string()));
+            }
+            '{' => {
+                chars.next();
+                let mut path = String::new();
+                loop {
+                    match chars.next() {
+                        Some((_, '}')) => break,
+                        Some((_, c)) => path.push(c),
+                        None => return Err("unterminated {".to_string()),
+                    }
+                }
+                tokens.push(Token::Path(path));
+            }
+            '+' | '-' | '*' | '/' => {
+                chars.next();
+                tokens.push(Token::Op(c));
+            }
+            '(' => {
+                chars.next();
+                tokens.push(Token::LParen);
+            }
+            ')' => {
+                chars.next();
+                tokens.push(Token::RParen);
+            }
+            ',' => {
+                chars.next();
+                tokens.push(Token::Comma);
+            }
+            other => return Err(format!("unexpected character '{}'", other)),
+        }
+    }
+
+    Ok(tokens)
+}
+
+/// End of the number starting at `start`. An exponent (`1e-3`, `2E6`) is only part of
+/// the number when digits follow the `e` and its optional sign.
+fn number_end(input: &str, start: usize) -> usize {
+    let bytes = input.as_bytes();
+    let digits_from = |mut i: usize, dot: bool| {
+        while i < bytes.len() && (bytes[i].is_ascii_digit() || (dot && bytes[i] == b'.')) {
+            i += 1;
+        }
+        i
+    };
+
+    let end = digits_from(start, true);
+    if !matches!(bytes.get(end), Some(b'e' | b'E')) {
+        return end;
+    }
+    let mut exponent = end + 1;
+    if matches!(bytes.get(exponent), Some(b'+' | b'-')) {
+        exponent += 1;
+    }
+    if bytes.get(exponent).is_some_and(u8::is_ascii_digit) {
+        digits_from(exponent, false)
+    } else {
+        end
+    }
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn expect(&mut self, expected: Token) -> std::result::Result<(), String> {
+        match self.next() {
+            Some(token) if token == expected => Ok(()),
+            Some(token) => Err(format!("expected {:?}, found {:?}", expected, token)),
+            None => Err(format!("expected {:?}, found end of input", expected)),
+        }
+    }
+
+    fn expr(&mut self) -> std::result::Result<Expr, String> {
+        let mut lhs = self.term()?;
+        while let Some(Token::Op(c @ ('+' | '-'))) = self.peek() {
+            let op = if *c == '+' {
+                BinaryOp::Add
+            } else {
+                BinaryOp::Sub
+            };
+            self.pos += 1;
+            lhs = Expr::Binary(Box::new(lhs), op, Box::new(self.term()?));
+        }
+        Ok(lhs)
+    }
+
+    fn term(&mut self) -> std::result::Result<Expr, String> {
+        let mut lhs = self.unary()?;
+        while let Some(Token::Op(c @ ('*' | '/'))) = self.peek() {
+            let op = if *c == '*' {
+                BinaryOp::Mul
+            } else {
+                BinaryOp::Div
+            };
+            self.pos += 1;
+            lhs = Expr::Binary(Box::new(lhs), op, Box::new(self.unary()?));
+        }
+        Ok(lhs)
+    }
+
+    fn unary(&mut self) -> std::result::Result<Expr, String> {
+        if let Some(Token::Op('-')) = self.peek() {
+            self.pos += 1;
+            return Ok(Expr::Neg(Box::new(self.unary()?)));
+        }
+        self.primary()
+    }
+
+    fn primary(&mut self) -> std::result::Result<Expr, String> {
+        match self.next() {
+            Some(Token::Number(n)) => Ok(Expr::Number(n)),
+            Some(Token::LParen) => {
+                let inner = self.expr()?;
+                self.expect(Token::RParen)?;
+                Ok(inner)
+            }
+            Some(Token::Path(path)) => MetricPath::parse(&path)
+                .map(Expr::Metric)
+                .ok_or_else(|| format!("invalid metric {}", path)),
+            Some(Token::Ident(name)) if self.peek() == Some(&Token::LParen) => {
+                let function = match name.as_str() {
+                    "min" => Function::Min,
+                    "max" => Function::Max,
+                    "abs" => Function::Abs,
+                    other => return Err(format!("unknown function {}", other)),
+                };
+                self.pos += 1;
+
+                let mut args = vec![self.expr()?];
+                while self.peek() == Some(&Token::Comma) {
+                    self.pos += 1;
+                    args.push(self.expr()?);
+                }
+                self.expect(Token::RParen)?;
+                Ok(Expr::Call(function, args))
+            }
+            Some(Token::Ident(name)) => MetricPath::parse(&name)
+                .map(Expr::Metric)
+                .ok_or_else(|| format!("expected module.metric, found {}", name)),
+            Some(token) => Err(format!("unexpected {:?}", token)),
+            None => Err("unexpected end of input".to_string()),
+        }
+    }
+}
+
+/// A named expression from the `derived` config list, written `name = expression`.
+#[derive(Debug, Clone)]
+pub struct DerivedMetric {
+    pub name: String,
+    pub expr: Expr,
+}
+
+impl DerivedMetric {
+    pub fn parse(definition: &str) -> std::result::Result<Self, String> {
+        let (name, expr) = definition
+            .split_once('=')
+            .ok_or_else(|| "expected `name = expression`".to_string())?;
+
+        let name = name.trim();
+        if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+            return Err(format!("invalid name '{}'", name));
+        }
+
+        Ok(Self {
+            name: name.to_string(),
+            expr: Expr::parse(expr)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::MetricData;
+    use std::time::SystemTime;
+
+    fn snapshot() -> MetricsSnapshot {
+        let metrics = [
+            ("used_memory_bytes", MetricValue::Integer(750)),
+            ("total_memory_bytes", MetricValue::Integer(1000)),
+            ("zero", MetricValue::Integer(0)),
+            ("brand", MetricValue::String("x".to_string())),
+            ("mount./var.usage_percent", MetricValue::Float(42.5)),
+        ];
+        MetricsSnapshot {
+            modules: vec![(
+                "memory".to_string(),
+                MetricData {
+                    timestamp: SystemTime::now(),
+                    metrics: metrics
+                        .into_iter()
+                        .map(|(k, v)| (k.to_string(), v))
+                        .collect(),
+                },
+            )],
+            alerts: Vec::new(),
+            anomalies: Vec::new(),
+        }
+    }
+
+    fn eval(input: &str) -> Option<f64> {
+        Expr::parse(input).unwrap().eval(&snapshot())
+    }
+
+    #[test]
+    fn tokenizes_numbers_with_exponents() {
+        assert_eq!(tokenize("1e-3").unwrap(), vec![Token::Number(1e-3)]);
+        assert_eq!(tokenize("2.5E+2").unwrap(), vec![Token::Number(250.0)]);
+        assert_eq!(tokenize(".5e3").unwrap(), vec![Token::Number(500.0)]);
+        assert_eq!(
+            tokenize("1e-3*2").unwrap(),
+            vec![Token::Number(1e-3), Token::Op('*'), Token::Number(2.0)]
+        );
+        // Without digits after it, the `e` is not part of the number.
+        assert_eq!(
+            tokenize("2e").unwrap(),
+            vec![Token::Number(2.0), Token::Ident("e".to_string())]
+        );
+        assert_eq!(
+            tokenize("2e-x").unwrap(),
+            vec![
+                Token::Number(2.0),
+                Token::Ident("e".to_string()),
+                Token::Op('-'),
+                Token::Ident("x".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn evaluates_arithmetic() {
+        assert_eq!(eval("1e-3 * 2"), Some(0.002));
+        assert_eq!(eval("1 + 2 * 3"), Some(7.0));
+        assert_eq!(eval("(1 + 2) * 3"), Some(9.0));
+        assert_eq!(eval("10 - 4 - 3"), Some(3.0));
+        assert_eq!(eval("-2 * -3"), Some(6.0));
+        assert_eq!(eval("min(4, 2, 8) + max(1, 5) + abs(-3)"), Some(10.0));
+    }
+
+    #[test]
+    fn evaluates_metric_references() {
+        assert_eq!(
+            eval("memory.used_memory_bytes / memory.total_memory_bytes * 100"),
+            Some(75.0)
+        );
+        assert_eq!(eval("{memory.mount./var.usage_percent} * 2"), Some(85.0));
+        let expr = Expr::parse("memory.used_memory_bytes + {memory.zero}").unwrap();
+        let paths: Vec<String> = expr.metric_paths().iter().map(|p| p.to_string()).collect();
+        assert_eq!(paths, vec!["memory.used_memory_bytes", "memory.zero"]);
+    }
+
+    #[test]
+    fn missing_values_and_division_by_zero_yield_nothing() {
+        assert_eq!(eval("memory.used_memory_bytes / memory.zero"), None);
+        assert_eq!(eval("memory.missing + 1"), None);
+        assert_eq!(eval("memory.brand + 1"), None);
+        assert_eq!(eval("cpu.cpu_usage_percent"), None);
+    }
+
+    #[test]
+    fn rejects_malformed_expressions() {
+        for bad in [
+            "2e", "1 +", "(1 + 2", "1 2", "foo(1)", "cpu", "{cpu", "1 % 2", "min()",
+        ] {
+            assert!(Expr::parse(bad).is_err(), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn parses_derived_definitions() {
+        let metric = DerivedMetric::parse("mem_ratio = memory.used_memory_bytes / 2").unwrap();
+        assert_eq!(metric.name, "mem_ratio");
+        assert_eq!(metric.expr.eval(&snapshot()), Some(375.0));
+        assert!(DerivedMetric::parse("memory.used_memory_bytes / 2").is_err());
+        assert!(DerivedMetric::parse("bad name = 1").is_err());
+    }
+}
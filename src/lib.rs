@@ -1,54 +1,99 @@
+pub mod alerts;
+pub mod anomaly;
+pub mod check;
 pub mod cli;
 pub mod config;
 pub mod core;
 pub mod engine;
 pub mod error;
+pub mod expr;
 pub mod modules;
 pub mod output;
+pub mod sinks;
 pub mod tui;
 
-use crate::cli::{Cli, Command};
-use crate::config::load_config;
+use std::io::Write;
+
+use crate::cli::{BarProtocolArg, Cli, Command};
+use crate::config::{load_config, BarProtocol};
+use crate::core::MetricPath;
 use crate::engine::Engine;
 use crate::error::Result;
-use crate::output::{format_snapshot, OutputFormat};
+use crate::output::{bar, format_snapshot, OutputFormat, OutputStyle};
+use crate::sinks::Sinks;
 
 pub fn run(args: Cli) -> Result<()> {
-    let config = if args.no_config {
-        config::Config::default()
+    // Check mode must report every failure as an UNKNOWN plugin result, config errors included.
+    if let Some(Command::Check { warn, crit }) = &args.command {
+        let outcome = config_for(&args).and_then(|config| check::run_check(&config, warn, crit));
+        return check::report(outcome);
+    }
+
+    let config = config_for(&args)?;
+    run_command(args, config)
+}
+
+fn config_for(args: &Cli) -> Result<config::Config> {
+    if args.no_config {
+        Ok(config::Config::default())
     } else {
-        load_config(args.config.as_deref())?
-    };
+        load_config(args.config.as_deref())
+    }
+}
 
+fn run_command(args: Cli, config: config::Config) -> Result<()> {
     match args.command {
         Some(Command::Tui { module }) => {
             let modules = resolve_modules(module, &config);
-            let engine = Engine::new(&modules)?;
+            let engine = Engine::new(&modules, &config)?;
             tui::run_tui(engine, config)
         }
+        Some(Command::Bar { protocol, metric }) => {
+            let selectors = metric.unwrap_or_else(|| config.bar.metrics.clone());
+            let metrics = bar::parse_metrics(&selectors)?;
+
+            let mut modules: Vec<String> = Vec::new();
+            for path in &metrics {
+                if !modules.contains(&path.module) {
+                    modules.push(path.module.clone());
+                }
+            }
+
+            let engine = Engine::new(&modules, &config)?;
+            let protocol = match protocol {
+                Some(BarProtocolArg::I3bar) => BarProtocol::I3bar,
+                Some(BarProtocolArg::Waybar) => BarProtocol::Waybar,
+                None => config.bar.protocol,
+            };
+            run_bar(engine, protocol, &metrics, &config)
+        }
+        Some(Command::Check { .. }) => unreachable!("check mode is reported by run"),
         Some(Command::Print {
             module,
             output,
             watch,
         }) => {
             let modules = resolve_modules(module, &config);
-            let engine = Engine::new(&modules)?;
+            let engine = Engine::new(&modules, &config)?;
             let format = match output {
                 Some(fmt) => OutputFormat::from(fmt),
                 None => OutputFormat::from_str_lossy(&config.print.output),
             };
 
+            let style = OutputStyle::from_config(&config);
+
             if watch || config.print.watch {
-                run_watch(engine, format, config.general.refresh_ms)
+                let sinks = Sinks::from_config(&config.sinks)?;
+                run_watch(engine, format, style, sinks, config.general.refresh_ms)
             } else {
-                run_print_once(engine, format)
+                run_print_once(engine, format, style)
             }
         }
         None => {
             let modules = resolve_modules(None, &config);
-            let engine = Engine::new(&modules)?;
+            let engine = Engine::new(&modules, &config)?;
             let format = OutputFormat::from_str_lossy(&config.print.output);
-            run_print_once(engine, format)
+            run_print_once(engine, format, OutputStyle::from_config(&config))
         }
     }
 }
@@ -57,13 +102,19 @@ fn resolve_modules(cli_modules: Option<Vec<String>>, config: &config::Config) ->
     cli_modules.unwrap_or_else(|| config.general.default_modules.clone())
 }
 
-fn run_print_once(engine: Engine, format: OutputFormat) -> Result<()> {
+fn run_print_once(mut engine: Engine, format: OutputFormat, style: OutputStyle) -> Result<()> {
     let snapshot = engine.collect_once();
-    print!("{}", format_snapshot(&snapshot, &format));
+    print!("{}", format_snapshot(&snapshot, &format, &style));
     Ok(())
 }
 
-fn run_watch(engine: Engine, format: OutputFormat, refresh_ms: u64) -> Result<()> {
+fn run_watch(
+    mut engine: Engine,
+    format: OutputFormat,
+    style: OutputStyle,
+    mut sinks: Sinks,
+    refresh_ms: u64,
+) -> Result<()> {
     let duration = std::time::Duration::from_millis(refresh_ms);
 
     crossterm::terminal::enable_raw_mode()?;
@@ -72,7 +123,8 @@ fn run_watch(engine: Engine, format: OutputFormat, refresh_ms: u64) -> Result<()
         loop {
             print!("\x1B[2J\x1B[1;1H");
             let snapshot = engine.collect_once();
-            print!("{}", format_snapshot(&snapshot, &format));
+            print!("{}", format_snapshot(&snapshot, &format, &style));
+            sinks.dispatch(&snapshot);
 
             if crossterm::event::poll(duration)? {
                 if let crossterm::event::Event::Key(key) = crossterm::event::read()? {
@@ -90,3 +142,38 @@ fn run_watch(engine: Engine, format: OutputFormat, refresh_ms: u64) -> Result<()
     crossterm::terminal::disable_raw_mode()?;
     result
 }
+
+fn run_bar(
+    mut engine: Engine,
+    protocol: BarProtocol,
+    metrics: &[MetricPath],
+    config: &config::Config,
+) -> Result<()> {
+    let duration = std::time::Duration::from_millis(config.bar_refresh_ms());
+    let mut stdout = std::io::stdout();
+
+    if protocol == BarProtocol::I3bar {
+        stdout.write_all(bar::i3bar_header().as_bytes())?;
+    }
+
+    let mut first = true;
+    loop {
+        let snapshot = engine.collect_once();
+        let line = match protocol {
+            BarProtocol::I3bar => bar::format_i3bar(&snapshot, metrics, config, first),
+            BarProtocol::Waybar => bar::format_waybar(&snapshot, metrics, config),
+        };
+        first = false;
+
+        // The bar closing our stdout is the normal way to be told to stop.
+        let written = stdout
+            .write_all(line.as_bytes())
+            .and_then(|_| stdout.flush());
+        match written {
+            Err(e) if e.kind() == std::io::ErrorKind::BrokenPipe => return Ok(()),
+            other => other?,
+        }
+
+        std::thread::sleep(duration);
+    }
+}
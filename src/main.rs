@@ -1,10 +1,28 @@
 use clap::Parser;
 
 fn main() {
-    let args = gim::cli::Cli::parse();
+    let args = match gim::cli::Cli::try_parse() {
+        Ok(args) => args,
+        // Plugin runners read exit code 2 as CRITICAL, so a usage error in check mode must
+        // come out as UNKNOWN with a status line instead of clap's own report.
+        Err(e) if e.use_stderr() && gim::cli::is_check_invocation(std::env::args()) => {
+            let rendered = e.to_string();
+            let reason = rendered
+                .lines()
+                .next()
+                .unwrap_or_default()
+                .trim_start_matches("error: ");
+            let reported = gim::check::report(Err(gim::check::unknown(reason)));
+            std::process::exit(reported.map_or_else(|e| e.exit_code(), |()| 0));
+        }
+        Err(e) => e.exit(),
+    };
 
     if let Err(e) = gim::run(args) {
-        eprintln!("error: {e}");
+        // Check results have already been reported on stdout as a plugin status line.
+        if !matches!(e, gim::error::GimError::Check { .. }) {
+            eprintln!("error: {e}");
+        }
         std::process::exit(e.exit_code());
     }
 }
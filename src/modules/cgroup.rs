@@ -0,0 +1,227 @@
+use crate::core::{MetricCollector, MetricData, MetricValue};
+use crate::modules::cgroupfs::{
+    current_cgroup, read_cpu_quota, read_io_stat, read_keyed, read_limit, unified_mount,
+};
+use crate::modules::delta::{rate, DeltaSampler};
+use std::collections::HashMap;
+use std::path::Path;
+
+#[derive(Default)]
+pub struct CgroupCollector {
+    counters: DeltaSampler<CgroupCounters>,
+}
+
+/// Cumulative counters of one cgroup that are reported as rates.
+#[derive(Debug, Clone, Copy, Default)]
+struct CgroupCounters {
+    cpu_usage_us: u64,
+    periods: u64,
+    throttled_periods: u64,
+    throttled_us: u64,
+    read_bytes: u64,
+    write_bytes: u64,
+    read_ops: u64,
+    write_ops: u64,
+}
+
+impl CgroupCollector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl MetricCollector for CgroupCollector {
+    fn collect(&self) -> Result<MetricData, Box<dyn std::error::Error>> {
+        let mount = unified_mount().ok_or("cgroup v2 is not mounted")?;
+        let dir = current_cgroup().ok_or("not running in a cgroup v2 hierarchy")?;
+
+        let mut metrics = HashMap::new();
+        let relative = dir.strip_prefix(&mount).unwrap_or(&dir);
+        metrics.insert(
+            "path".to_string(),
+            MetricValue::String(format!("/{}", relative.display())),
+        );
+
+        insert_memory(&dir, &mut metrics);
+
+        let quota = read_cpu_quota(&dir);
+        if let Some(cores) = quota {
+            metrics.insert("cpu_quota_cores".to_string(), MetricValue::Float(cores));
+        }
+
+        if let Some(current) = read_limit(&dir, "pids.current") {
+            metrics.insert(
+                "pids_current".to_string(),
+                MetricValue::Integer(current as i64),
+            );
+        }
+        if let Some(max) = read_limit(&dir, "pids.max") {
+            metrics.insert("pids_max".to_string(), MetricValue::Integer(max as i64));
+        }
+
+        if let Ok((before, after, elapsed)) = self.counters.sample(|| read_counters(&dir)) {
+            insert_rates(&before, &after, elapsed, quota, &mut metrics);
+        }
+
+        Ok(MetricData {
+            timestamp: std::time::SystemTime::now(),
+            metrics,
+        })
+    }
+
+    fn name(&self) -> &'static str {
+        "cgroup"
+    }
+
+    fn prime(&self) -> bool {
+        current_cgroup().is_some_and(|dir| self.counters.prime(|| read_counters(&dir)))
+    }
+}
+
+fn insert_memory(dir: &Path, metrics: &mut HashMap<String, MetricValue>) {
+    let current = read_limit(dir, "memory.current");
+    let max = read_limit(dir, "memory.max");
+
+    if let Some(current) = current {
+        metrics.insert(
+            "memory_current_bytes".to_string(),
+            MetricValue::Integer(current as i64),
+        );
+    }
+    if let Some(max) = max {
+        metrics.insert(
+            "memory_max_bytes".to_string(),
+            MetricValue::Integer(max as i64),
+        );
+    }
+    if let Some(high) = read_limit(dir, "memory.high") {
+        metrics.insert(
+            "memory_high_bytes".to_string(),
+            MetricValue::Integer(high as i64),
+        );
+    }
+    if let (Some(current), Some(max)) = (current, max) {
+        if max > 0 {
+            let percent = current as f64 / max as f64 * 100.0;
+            metrics.insert(
+                "memory_usage_percent".to_string(),
+                MetricValue::Float(percent),
+            );
+        }
+    }
+    if let Some(swap) = read_limit(dir, "memory.swap.current") {
+        metrics.insert(
+            "swap_current_bytes".to_string(),
+            MetricValue::Integer(swap as i64),
+        );
+    }
+    if let Some(swap_max) = read_limit(dir, "memory.swap.max") {
+        metrics.insert(
+            "swap_max_bytes".to_string(),
+            MetricValue::Integer(swap_max as i64),
+        );
+    }
+    if let Some(oom_kills) =
+        read_keyed(dir, "memory.events").and_then(|e| e.get("oom_kill").copied())
+    {
+        metrics.insert(
+            "oom_kills_total".to_string(),
+            MetricValue::Integer(oom_kills as i64),
+        );
+    }
+}
+
+fn read_counters(dir: &Path) -> Result<CgroupCounters, &'static str> {
+    let cpu = read_keyed(dir, "cpu.stat").ok_or("cpu.stat is not readable")?;
+    let io = read_io_stat(dir).unwrap_or_default();
+    let cpu_stat = |key: &str| cpu.get(key).copied().unwrap_or(0);
+    let io_stat = |key: &str| io.get(key).copied().unwrap_or(0);
+
+    Ok(CgroupCounters {
+        cpu_usage_us: cpu_stat("usage_usec"),
+        periods: cpu_stat("nr_periods"),
+        throttled_periods: cpu_stat("nr_throttled"),
+        throttled_us: cpu_stat("throttled_usec"),
+        read_bytes: io_stat("rbytes"),
+        write_bytes: io_stat("wbytes"),
+        read_ops: io_stat("rios"),
+        write_ops: io_stat("wios"),
+    })
+}
+
+fn insert_rates(
+    before: &CgroupCounters,
+    after: &CgroupCounters,
+    elapsed: f64,
+    quota: Option<f64>,
+    metrics: &mut HashMap<String, MetricValue>,
+) {
+    let mut insert = |key: &str, value: MetricValue| {
+        metrics.insert(key.to_string(), value);
+    };
+
+    // CPU time used per second of wall time, i.e. the number of cores kept busy.
+    let cores = rate(before.cpu_usage_us, after.cpu_usage_us, elapsed) / 1e6;
+    insert(
+        "cpu_usage_seconds_total",
+        MetricValue::Float(after.cpu_usage_us as f64 / 1e6),
+    );
+    insert("cpu_usage_cores", MetricValue::Float(cores));
+    if let Some(quota) = quota.filter(|q| *q > 0.0) {
+        insert(
+            "cpu_quota_usage_percent",
+            MetricValue::Float(cores / quota * 100.0),
+        );
+    }
+
+    let periods = after.periods.saturating_sub(before.periods);
+    let throttled = after
+        .throttled_periods
+        .saturating_sub(before.throttled_periods);
+    let throttled_percent = if periods > 0 {
+        throttled as f64 / periods as f64 * 100.0
+    } else {
+        0.0
+    };
+    insert(
+        "cpu_periods_total",
+        MetricValue::Integer(after.periods as i64),
+    );
+    insert(
+        "cpu_throttled_periods_total",
+        MetricValue::Integer(after.throttled_periods as i64),
+    );
+    insert(
+        "cpu_throttled_seconds_total",
+        MetricValue::Float(after.throttled_us as f64 / 1e6),
+    );
+    insert(
+        "cpu_throttled_percent",
+        MetricValue::Float(throttled_percent),
+    );
+
+    insert(
+        "io_read_bytes_total",
+        MetricValue::Integer(after.read_bytes as i64),
+    );
+    insert(
+        "io_write_bytes_total",
+        MetricValue::Integer(after.write_bytes as i64),
+    );
+    insert(
+        "io_read_bytes_per_sec",
+        MetricValue::Float(rate(before.read_bytes, after.read_bytes, elapsed)),
+    );
+    insert(
+        "io_write_bytes_per_sec",
+        MetricValue::Float(rate(before.write_bytes, after.write_bytes, elapsed)),
+    );
+    insert(
+        "io_read_iops",
+        MetricValue::Float(rate(before.read_ops, after.read_ops, elapsed)),
+    );
+    insert(
+        "io_write_iops",
+        MetricValue::Float(rate(before.write_ops, after.write_ops, elapsed)),
+    );
+}
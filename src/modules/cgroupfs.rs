@@ -0,0 +1,231 @@
+use crate::core::MetricValue;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Where the cgroup v2 hierarchy is mounted: `/sys/fs/cgroup` on unified systems, or
+/// `/sys/fs/cgroup/unified` on systemd's hybrid layout. `None` on cgroup v1-only hosts.
+pub(crate) fn unified_mount() -> Option<PathBuf> {
+    ["/sys/fs/cgroup", "/sys/fs/cgroup/unified"]
+        .iter()
+        .map(PathBuf::from)
+        .find(|dir| dir.join("cgroup.controllers").exists())
+}
+
+/// Directory of the cgroup gim itself runs in, from the `0::<path>` entry of
+/// `/proc/self/cgroup`. Inside a container with a cgroup namespace this is the
+/// container's own cgroup, which appears as the mount root.
+pub(crate) fn current_cgroup() -> Option<PathBuf> {
+    let mount = unified_mount()?;
+    let membership = std::fs::read_to_string("/proc/self/cgroup").ok()?;
+    let path = membership
+        .lines()
+        .find_map(|line| line.strip_prefix("0::"))?;
+    Some(mount.join(path.trim().trim_start_matches('/')))
+}
+
+/// The host's root cgroup has no `cgroup.type` and none of the limit files; a
+/// namespaced root (a container's cgroup) is a regular cgroup and has both.
+pub(crate) fn is_host_root(dir: &Path) -> bool {
+    !dir.join("cgroup.type").exists()
+}
+
+pub(crate) fn read_string(dir: &Path, file: &str) -> Option<String> {
+    std::fs::read_to_string(dir.join(file))
+        .ok()
+        .map(|s| s.trim().to_string())
+}
+
+/// Reads a single-value file such as `memory.current` or `memory.max`; `max` means
+/// unlimited and yields `None`.
+pub(crate) fn read_limit(dir: &Path, file: &str) -> Option<u64> {
+    parse_limit(&read_string(dir, file)?)
+}
+
+fn parse_limit(contents: &str) -> Option<u64> {
+    contents.trim().parse().ok()
+}
+
+/// Parses flat keyed files like `cpu.stat` or `memory.events` (`key value` per line).
+pub(crate) fn read_keyed(dir: &Path, file: &str) -> Option<HashMap<String, u64>> {
+    let contents = std::fs::read_to_string(dir.join(file)).ok()?;
+    Some(parse_keyed(&contents))
+}
+
+fn parse_keyed(contents: &str) -> HashMap<String, u64> {
+    contents
+        .lines()
+        .filter_map(|line| {
+            let (key, value) = line.split_once(' ')?;
+            Some((key.to_string(), value.trim().parse().ok()?))
+        })
+        .collect()
+}
+
+/// CPU bandwidth limit from `cpu.max` (`<quota> <period>`) as a number of cores;
+/// `None` when the quota is `max`.
+pub(crate) fn read_cpu_quota(dir: &Path) -> Option<f64> {
+    parse_cpu_quota(&read_string(dir, "cpu.max")?)
+}
+
+fn parse_cpu_quota(cpu_max: &str) -> Option<f64> {
+    let (quota, period) = cpu_max.trim().split_once(' ')?;
+    let quota: f64 = quota.parse().ok()?;
+    let period: f64 = period.parse().ok()?;
+    (period > 0.0).then(|| quota / period)
+}
+
+/// Summed over devices from `io.stat` lines like
+/// `8:0 rbytes=1 wbytes=2 rios=3 wios=4 dbytes=0 dios=0`.
+pub(crate) fn read_io_stat(dir: &Path) -> Option<HashMap<String, u64>> {
+    let contents = std::fs::read_to_string(dir.join("io.stat")).ok()?;
+    Some(parse_io_stat(&contents))
+}
+
+fn parse_io_stat(contents: &str) -> HashMap<String, u64> {
+    let mut totals = HashMap::new();
+    for (key, value) in contents
+        .lines()
+        .flat_map(|line| line.split_whitespace().skip(1))
+        .filter_map(|field| field.split_once('='))
+    {
+        if let Ok(value) = value.parse::<u64>() {
+            *totals.entry(key.to_string()).or_insert(0) += value;
+        }
+    }
+    totals
+}
+
+/// One `some` or `full` line of a PSI file.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct PressureLine {
+    pub avg10: f64,
+    pub avg60: f64,
+    pub avg300: f64,
+    pub total_us: u64,
+}
+
+/// Reads a PSI file (`/proc/pressure/cpu`, `<cgroup>/memory.pressure`, ...) as
+/// `(kind, line)` pairs where kind is `some` or `full`.
+pub(crate) fn read_pressure(path: &Path) -> Option<Vec<(String, PressureLine)>> {
+    let contents = std::fs::read_to_string(path).ok()?;
+    Some(parse_pressure(&contents))
+}
+
+/// Parses PSI lines like `some avg10=0.12 avg60=0.05 avg300=0.01 total=123456`. Lines
+/// missing a field are skipped.
+fn parse_pressure(contents: &str) -> Vec<(String, PressureLine)> {
+    contents
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let kind = fields.next()?.to_string();
+            let mut values: HashMap<&str, &str> =
+                fields.filter_map(|f| f.split_once('=')).collect();
+            let mut take = |name: &str| values.remove(name);
+            Some((
+                kind,
+                PressureLine {
+                    avg10: take("avg10")?.parse().ok()?,
+                    avg60: take("avg60")?.parse().ok()?,
+                    avg300: take("avg300")?.parse().ok()?,
+                    total_us: take("total")?.parse().ok()?,
+                },
+            ))
+        })
+        .collect()
+}
+
+/// Publishes `<prefix>.<kind>.{avg10,avg60,avg300,stall_seconds_total}`.
+pub(crate) fn insert_pressure(
+    metrics: &mut HashMap<String, MetricValue>,
+    prefix: &str,
+    lines: &[(String, PressureLine)],
+) {
+    for (kind, line) in lines {
+        let key = format!("{}.{}", prefix, kind);
+        metrics.insert(format!("{}.avg10", key), MetricValue::Float(line.avg10));
+        metrics.insert(format!("{}.avg60", key), MetricValue::Float(line.avg60));
+        metrics.insert(format!("{}.avg300", key), MetricValue::Float(line.avg300));
+        metrics.insert(
+            format!("{}.stall_seconds_total", key),
+            MetricValue::Float(line.total_us as f64 / 1e6),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cpu_quota_in_cores() {
+        assert_eq!(parse_cpu_quota("200000 100000\n"), Some(2.0));
+        assert_eq!(parse_cpu_quota("50000 100000"), Some(0.5));
+        assert_eq!(parse_cpu_quota("max 100000\n"), None);
+        assert_eq!(parse_cpu_quota("100000 0"), None);
+    }
+
+    #[test]
+    fn max_means_no_limit() {
+        assert_eq!(parse_limit("max\n"), None);
+        assert_eq!(parse_limit("536870912\n"), Some(536_870_912));
+    }
+
+    #[test]
+    fn parses_keyed_files() {
+        let stat = parse_keyed("anon 1048576\nfile 4194304\ninactive_file 3145728\nbogus\n");
+        assert_eq!(stat.len(), 3);
+        assert_eq!(stat["inactive_file"], 3_145_728);
+    }
+
+    #[test]
+    fn sums_io_stat_over_devices() {
+        let io = parse_io_stat(
+            "8:0 rbytes=4096 wbytes=8192 rios=1 wios=2 dbytes=0 dios=0\n\
+             259:0 rbytes=1024 wbytes=0 rios=3 wios=0 dbytes=512 dios=1\n\
+             253:1 rbytes=bogus wbytes=100\n",
+        );
+        assert_eq!(io["rbytes"], 5120);
+        assert_eq!(io["wbytes"], 8292);
+        assert_eq!(io["rios"], 4);
+        assert_eq!(io["dbytes"], 512);
+        assert!(parse_io_stat("").is_empty());
+    }
+
+    #[test]
+    fn parses_some_and_full_pressure() {
+        let lines = parse_pressure(
+            "some avg10=1.25 avg60=0.50 avg300=0.10 total=2500000\n\
+             full avg10=0.75 avg60=0.25 avg300=0.00 total=1000000\n",
+        );
+        assert_eq!(lines.len(), 2);
+        let (kind, some) = &lines[0];
+        assert_eq!(kind, "some");
+        assert_eq!((some.avg10, some.avg60, some.avg300), (1.25, 0.5, 0.1));
+        assert_eq!(some.total_us, 2_500_000);
+        assert_eq!(lines[1].0, "full");
+        assert_eq!(lines[1].1.total_us, 1_000_000);
+    }
+
+    #[test]
+    fn cpu_pressure_may_have_no_full_line() {
+        // Kernels before 5.13 report only `some` for cpu.
+        let lines = parse_pressure("some avg10=0.00 avg60=0.02 avg300=0.00 total=41241\n");
+        assert_eq!(lines.len(), 1);
+        assert_eq!(lines[0].0, "some");
+
+        let mut metrics = HashMap::new();
+        insert_pressure(&mut metrics, "cpu", &lines);
+        assert!(matches!(
+            metrics["cpu.some.stall_seconds_total"],
+            MetricValue::Float(s) if s == 0.041241
+        ));
+        assert!(metrics.keys().all(|key| !key.starts_with("cpu.full")));
+    }
+
+    #[test]
+    fn skips_incomplete_pressure_lines() {
+        let lines = parse_pressure("some avg10=0.00 avg60=0.00\nfull garbage\n\n");
+        assert!(lines.is_empty());
+    }
+}
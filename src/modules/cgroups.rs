@@ -0,0 +1,278 @@
+use crate::config::CgroupsConfig;
+use crate::core::{MetricCollector, MetricData, MetricValue};
+use crate::modules::cgroupfs::{read_io_stat, read_keyed, read_limit, unified_mount};
+use crate::modules::delta::{rate, DeltaSampler};
+use std::collections::{BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+
+/// Ranks the cgroups below the hierarchy root (systemd slices, services, scopes,
+/// containers) by CPU, memory and IO.
+#[derive(Default)]
+pub struct CgroupsCollector {
+    config: CgroupsConfig,
+    counters: DeltaSampler<HashMap<String, Counters>>,
+}
+
+/// Cumulative CPU time and IO bytes of one cgroup.
+#[derive(Debug, Clone, Copy, Default)]
+struct Counters {
+    cpu_usage_us: u64,
+    io_bytes: u64,
+}
+
+struct CgroupUsage {
+    path: String,
+    cpu_cores: f64,
+    memory_bytes: Option<u64>,
+    io_bytes_per_sec: f64,
+}
+
+impl CgroupsCollector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_config(config: CgroupsConfig) -> Self {
+        CgroupsCollector {
+            config,
+            counters: DeltaSampler::default(),
+        }
+    }
+}
+
+impl MetricCollector for CgroupsCollector {
+    fn collect(&self) -> Result<MetricData, Box<dyn std::error::Error>> {
+        let root = unified_mount().ok_or("cgroup v2 is not mounted")?;
+        let depth = self.config.depth.max(1);
+
+        let mut dirs = Vec::new();
+        walk(&root, depth, &mut dirs);
+        let (before, after, elapsed) = self
+            .counters
+            .sample(|| read_all_counters(&root, &dirs))
+            .map_err(|_| "failed to sample cgroup counters")?;
+
+        let usages: Vec<CgroupUsage> = dirs
+            .iter()
+            .map(|dir| {
+                let path = relative_path(&root, dir);
+                let now = after.get(&path).copied().unwrap_or_default();
+                // Cgroups created since the last sample have nothing to compare against yet.
+                let then = before.get(&path).copied().unwrap_or(now);
+                CgroupUsage {
+                    cpu_cores: rate(then.cpu_usage_us, now.cpu_usage_us, elapsed) / 1e6,
+                    memory_bytes: read_limit(dir, "memory.current"),
+                    io_bytes_per_sec: rate(then.io_bytes, now.io_bytes, elapsed),
+                    path,
+                }
+            })
+            .collect();
+
+        let top_n = self.config.top_n;
+        let by_cpu = top_by(&usages, top_n, |u| u.cpu_cores);
+        let by_memory = top_by(&usages, top_n, |u| u.memory_bytes.unwrap_or(0) as f64);
+        let by_io = top_by(&usages, top_n, |u| u.io_bytes_per_sec);
+
+        let mut metrics = HashMap::new();
+        metrics.insert(
+            "cgroup_count".to_string(),
+            MetricValue::Integer(usages.len() as i64),
+        );
+        metrics.insert(
+            "top_by_cpu".to_string(),
+            summarize(&by_cpu, |u| {
+                format!("{} — {:.2} cores", u.path, u.cpu_cores)
+            }),
+        );
+        metrics.insert(
+            "top_by_memory".to_string(),
+            summarize(&by_memory, |u| {
+                format!(
+                    "{} — {:.1}MB",
+                    u.path,
+                    u.memory_bytes.unwrap_or(0) as f64 / 1_048_576.0
+                )
+            }),
+        );
+        metrics.insert(
+            "top_by_io".to_string(),
+            summarize(&by_io, |u| {
+                format!("{} — {:.1}MB/s", u.path, u.io_bytes_per_sec / 1_048_576.0)
+            }),
+        );
+
+        // Details for every cgroup that made any of the lists.
+        let ranked: BTreeSet<&str> = by_cpu
+            .iter()
+            .chain(&by_memory)
+            .chain(&by_io)
+            .map(|u| u.path.as_str())
+            .collect();
+        for usage in usages.iter().filter(|u| ranked.contains(u.path.as_str())) {
+            let prefix = format!("cgroup.{}", usage.path);
+            metrics.insert(
+                format!("{}.cpu_usage_cores", prefix),
+                MetricValue::Float(usage.cpu_cores),
+            );
+            if let Some(bytes) = usage.memory_bytes {
+                metrics.insert(
+                    format!("{}.memory_current_bytes", prefix),
+                    MetricValue::Integer(bytes as i64),
+                );
+            }
+            metrics.insert(
+                format!("{}.io_bytes_per_sec", prefix),
+                MetricValue::Float(usage.io_bytes_per_sec),
+            );
+        }
+
+        Ok(MetricData {
+            timestamp: std::time::SystemTime::now(),
+            metrics,
+        })
+    }
+
+    fn name(&self) -> &'static str {
+        "cgroups"
+    }
+
+    fn prime(&self) -> bool {
+        let Some(root) = unified_mount() else {
+            return false;
+        };
+        let mut dirs = Vec::new();
+        walk(&root, self.config.depth.max(1), &mut dirs);
+        self.counters.prime(|| read_all_counters(&root, &dirs))
+    }
+}
+
+/// Collects the cgroups that are leaves of the tree cut off `depth` levels below `dir`:
+/// those without child cgroups, or at the depth limit. A parent's usage includes its
+/// children's, so ranking both would count the same work twice.
+fn walk(dir: &Path, depth: usize, found: &mut Vec<PathBuf>) {
+    if depth == 0 {
+        return;
+    }
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    let mut children: Vec<PathBuf> = entries
+        .flatten()
+        .filter(|e| e.file_type().map(|t| t.is_dir()).unwrap_or(false))
+        .map(|e| e.path())
+        .collect();
+    children.sort();
+
+    for child in children {
+        let before = found.len();
+        walk(&child, depth - 1, found);
+        if found.len() == before {
+            found.push(child);
+        }
+    }
+}
+
+fn relative_path(root: &Path, dir: &Path) -> String {
+    dir.strip_prefix(root).unwrap_or(dir).display().to_string()
+}
+
+fn read_all_counters(root: &Path, dirs: &[PathBuf]) -> Result<HashMap<String, Counters>, ()> {
+    Ok(dirs
+        .iter()
+        .map(|dir| (relative_path(root, dir), read_counters(dir)))
+        .collect())
+}
+
+fn read_counters(dir: &Path) -> Counters {
+    let io = read_io_stat(dir).unwrap_or_default();
+    Counters {
+        cpu_usage_us: read_keyed(dir, "cpu.stat")
+            .and_then(|stat| stat.get("usage_usec").copied())
+            .unwrap_or(0),
+        io_bytes: io.get("rbytes").copied().unwrap_or(0) + io.get("wbytes").copied().unwrap_or(0),
+    }
+}
+
+fn top_by(
+    usages: &[CgroupUsage],
+    n: usize,
+    key: impl Fn(&CgroupUsage) -> f64,
+) -> Vec<&CgroupUsage> {
+    let mut sorted: Vec<&CgroupUsage> = usages.iter().collect();
+    sorted.sort_by(|a, b| key(b).total_cmp(&key(a)));
+    sorted.truncate(n);
+    sorted
+}
+
+fn summarize(usages: &[&CgroupUsage], line: impl Fn(&CgroupUsage) -> String) -> MetricValue {
+    MetricValue::List(
+        usages
+            .iter()
+            .map(|u| MetricValue::String(line(u)))
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn usage(path: &str, cpu_cores: f64, memory_bytes: Option<u64>) -> CgroupUsage {
+        CgroupUsage {
+            path: path.to_string(),
+            cpu_cores,
+            memory_bytes,
+            io_bytes_per_sec: 0.0,
+        }
+    }
+
+    #[test]
+    fn walks_only_the_leaves_within_depth() {
+        let root = std::env::temp_dir().join(format!("gim-cgroups-{}", std::process::id()));
+        for dir in [
+            "system.slice/sshd.service",
+            "system.slice/cron.service",
+            "init.scope",
+            "user.slice/user-1000.slice/session-2.scope",
+        ] {
+            std::fs::create_dir_all(root.join(dir)).unwrap();
+        }
+        std::fs::write(root.join("cgroup.procs"), "").unwrap();
+
+        let mut found = Vec::new();
+        walk(&root, 2, &mut found);
+        let paths: Vec<String> = found.iter().map(|dir| relative_path(&root, dir)).collect();
+        std::fs::remove_dir_all(&root).unwrap();
+
+        // Parents are left out; user-1000.slice is cut off at the depth limit.
+        assert_eq!(
+            paths,
+            [
+                "init.scope",
+                "system.slice/cron.service",
+                "system.slice/sshd.service",
+                "user.slice/user-1000.slice",
+            ]
+        );
+    }
+
+    #[test]
+    fn ranks_the_top_n_by_key() {
+        let usages = [
+            usage("a.service", 0.5, Some(300)),
+            usage("b.service", 2.0, None),
+            usage("c.service", 1.0, Some(100)),
+        ];
+
+        let by_cpu: Vec<&str> = top_by(&usages, 2, |u| u.cpu_cores)
+            .iter()
+            .map(|u| u.path.as_str())
+            .collect();
+        assert_eq!(by_cpu, ["b.service", "c.service"]);
+
+        let by_memory = top_by(&usages, 10, |u| u.memory_bytes.unwrap_or(0) as f64);
+        assert_eq!(by_memory.len(), 3);
+        assert_eq!(by_memory[0].path, "a.service");
+        assert_eq!(by_memory[2].path, "b.service");
+    }
+}
@@ -1,12 +1,204 @@
 use crate::core::{MetricCollector, MetricData, MetricValue};
-use std::collections::HashMap;
+use crate::modules::cgroupfs::{current_cgroup, read_cpu_quota, read_keyed};
+use crate::modules::delta::{rate, DeltaSampler};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::Path;
+use std::sync::OnceLock;
 use sysinfo::{CpuRefreshKind, System, RefreshKind};
 
-pub struct CpuCollector;
+const CPU_SYSFS_DIR: &str = "/sys/devices/system/cpu";
+
+// CPU flags worth surfacing in capacity reports; the full list runs to hundreds.
+const NOTABLE_FLAGS: &[&str] = &[
+    "vmx",
+    "svm",
+    "hypervisor",
+    "avx",
+    "avx2",
+    "avx512f",
+    "avx512bw",
+    "avx512vl",
+    "avx512_vnni",
+    "avx_vnni",
+    "fma",
+    "aes",
+    "sha_ni",
+    "sse4_2",
+];
+
+// States published as `<state>_percent`; guest_nice is folded into guest.
+const CPU_STATES: &[&str] = &[
+    "user", "nice", "system", "idle", "iowait", "irq", "softirq", "steal", "guest",
+];
+
+#[derive(Default)]
+pub struct CpuCollector {
+    stat: DeltaSampler<ProcStat>,
+    topology: OnceLock<CpuTopology>,
+    cgroup_limits: bool,
+    cgroup_usage: DeltaSampler<u64>,
+}
+
+/// Hardware facts that don't change while gim runs, read once on first collection.
+#[derive(Debug, Default)]
+struct CpuTopology {
+    physical_cores: Option<usize>,
+    sockets: Option<usize>,
+    /// Total cache per level/type, e.g. `l1d`, `l2`, summed over distinct cache instances.
+    cache_bytes: BTreeMap<String, u64>,
+    /// Per-core (min, max) frequency limits in MHz from cpufreq.
+    frequency_limits: Vec<Option<(u64, u64)>>,
+    flags: Vec<String>,
+}
+
+/// Cumulative counters from `/proc/stat`; CPU times are in clock ticks.
+#[derive(Debug, Clone, Default)]
+struct ProcStat {
+    total: [u64; 10],
+    cores: Vec<[u64; 10]>,
+    context_switches: u64,
+    interrupts: u64,
+}
 
 impl CpuCollector {
     pub fn new() -> Self {
-        CpuCollector
+        Self::default()
+    }
+
+    /// Reports usage as a share of the cgroup's `cpu.max` quota when one is set, so a
+    /// container limited to 2 CPUs shows 100% when it uses both.
+    pub fn with_cgroup_limits(cgroup_limits: bool) -> Self {
+        CpuCollector {
+            cgroup_limits,
+            ..Self::default()
+        }
+    }
+
+    fn cgroup_usage_percent(&self) -> Option<f64> {
+        let dir = current_cgroup()?;
+        let quota = read_cpu_quota(&dir).filter(|q| *q > 0.0)?;
+        let (before, after, elapsed) = self.cgroup_usage.sample(|| read_cgroup_usage(&dir)).ok()?;
+        Some(quota_usage_percent(quota, before, after, elapsed))
+    }
+
+    fn collect_hardware(&self, sys: &System, metrics: &mut HashMap<String, MetricValue>) {
+        let cpus = sys.cpus();
+        let topology = self.topology.get_or_init(|| read_topology(cpus.len()));
+
+        if let Some(vendor) = cpus
+            .first()
+            .map(|c| c.vendor_id())
+            .filter(|v| !v.is_empty())
+        {
+            metrics.insert(
+                "cpu_vendor".to_string(),
+                MetricValue::String(vendor.to_string()),
+            );
+        }
+        metrics.insert(
+            "logical_core_count".to_string(),
+            MetricValue::Integer(cpus.len() as i64),
+        );
+        if let Some(cores) = topology.physical_cores {
+            metrics.insert(
+                "physical_core_count".to_string(),
+                MetricValue::Integer(cores as i64),
+            );
+        }
+        if let Some(sockets) = topology.sockets {
+            metrics.insert(
+                "socket_count".to_string(),
+                MetricValue::Integer(sockets as i64),
+            );
+        }
+        for (cache, bytes) in &topology.cache_bytes {
+            metrics.insert(
+                format!("cache.{}_bytes", cache),
+                MetricValue::Integer(*bytes as i64),
+            );
+        }
+
+        for (i, cpu) in cpus.iter().enumerate() {
+            let prefix = format!("core.{}", i);
+            if cpu.frequency() > 0 {
+                metrics.insert(
+                    format!("{}.frequency_mhz", prefix),
+                    MetricValue::Integer(cpu.frequency() as i64),
+                );
+            }
+            if let Some(Some((min, max))) = topology.frequency_limits.get(i) {
+                metrics.insert(
+                    format!("{}.min_frequency_mhz", prefix),
+                    MetricValue::Integer(*min as i64),
+                );
+                metrics.insert(
+                    format!("{}.max_frequency_mhz", prefix),
+                    MetricValue::Integer(*max as i64),
+                );
+            }
+        }
+
+        let has = |flag: &str| topology.flags.iter().any(|f| f == flag);
+        let virtualization = if has("vmx") {
+            "vt-x"
+        } else if has("svm") {
+            "amd-v"
+        } else {
+            "none"
+        };
+        metrics.insert(
+            "virtualization".to_string(),
+            MetricValue::String(virtualization.to_string()),
+        );
+        metrics.insert(
+            "hypervisor".to_string(),
+            MetricValue::Boolean(has("hypervisor")),
+        );
+        metrics.insert(
+            "flags".to_string(),
+            MetricValue::List(
+                topology
+                    .flags
+                    .iter()
+                    .cloned()
+                    .map(MetricValue::String)
+                    .collect(),
+            ),
+        );
+    }
+
+    fn collect_times(
+        &self,
+        metrics: &mut HashMap<String, MetricValue>,
+        before: &ProcStat,
+        after: &ProcStat,
+        elapsed: f64,
+    ) {
+        insert_breakdown(metrics, "", &state_ticks(&before.total, &after.total));
+        for (i, (then, now)) in before.cores.iter().zip(&after.cores).enumerate() {
+            insert_breakdown(metrics, &format!("core.{}.", i), &state_ticks(then, now));
+        }
+
+        metrics.insert(
+            "context_switches_total".to_string(),
+            MetricValue::Integer(after.context_switches as i64),
+        );
+        metrics.insert(
+            "interrupts_total".to_string(),
+            MetricValue::Integer(after.interrupts as i64),
+        );
+        metrics.insert(
+            "context_switches_per_sec".to_string(),
+            MetricValue::Float(rate(
+                before.context_switches,
+                after.context_switches,
+                elapsed,
+            )),
+        );
+        metrics.insert(
+            "interrupts_per_sec".to_string(),
+            MetricValue::Float(rate(before.interrupts, after.interrupts, elapsed)),
+        );
     }
 }
 
@@ -17,28 +209,52 @@ impl MetricCollector for CpuCollector {
                 .with_cpu(CpuRefreshKind::everything()),
         );
 
-        // Sleep briefly to allow CPU usage to be calculated accurately
-        std::thread::sleep(std::time::Duration::from_millis(200));
-        sys.refresh_cpu_all();
+        // Usage comes from the same /proc/stat interval as the per-state breakdown, so the
+        // two always agree. Without /proc/stat, let sysinfo measure its own short window.
+        let times = self.stat.sample(read_proc_stat).ok();
+        let core_usage: Vec<f64> = match &times {
+            Some((before, after, _)) => before
+                .cores
+                .iter()
+                .zip(&after.cores)
+                .map(|(then, now)| busy_percent(&state_ticks(then, now)))
+                .collect(),
+            None => {
+                std::thread::sleep(std::time::Duration::from_millis(200));
+                sys.refresh_cpu_all();
+                sys.cpus()
+                    .iter()
+                    .map(|cpu| cpu.cpu_usage() as f64)
+                    .collect()
+            }
+        };
+        let avg_usage = match &times {
+            Some((before, after, _)) => busy_percent(&state_ticks(&before.total, &after.total)),
+            None if core_usage.is_empty() => 0.0,
+            None => core_usage.iter().sum::<f64>() / core_usage.len() as f64,
+        };
 
         let mut metrics = HashMap::new();
 
         let cpus = sys.cpus();
-        let avg_usage: f64 = if cpus.is_empty() {
-            0.0
-        } else {
-            cpus.iter().map(|cpu| cpu.cpu_usage() as f64).sum::<f64>() / cpus.len() as f64
-        };
 
-        metrics.insert("cpu_usage_percent".to_string(), MetricValue::Float(avg_usage));
+        let cgroup_usage = self
+            .cgroup_limits
+            .then(|| self.cgroup_usage_percent())
+            .flatten();
+        metrics.insert(
+            "cpu_usage_percent".to_string(),
+            MetricValue::Float(cgroup_usage.unwrap_or(avg_usage)),
+        );
+        if cgroup_usage.is_some() {
+            metrics.insert("limited_by_cgroup".to_string(), MetricValue::Boolean(true));
+        }
         metrics.insert("cpu_count".to_string(), MetricValue::Integer(cpus.len() as i64));
 
-        let per_core: Vec<MetricValue> = cpus
+        let per_core: Vec<MetricValue> = core_usage
             .iter()
             .enumerate()
-            .map(|(i, cpu)| {
-                MetricValue::String(format!("core_{}: {:.1}%", i, cpu.cpu_usage()))
-            })
+            .map(|(i, usage)| MetricValue::String(format!("core_{}: {:.1}%", i, usage)))
             .collect();
         metrics.insert("per_core_usage".to_string(), MetricValue::List(per_core));
 
@@ -46,6 +262,11 @@ impl MetricCollector for CpuCollector {
             metrics.insert("cpu_brand".to_string(), MetricValue::String(first_cpu.brand().to_string()));
         }
 
+        self.collect_hardware(&sys, &mut metrics);
+        if let Some((before, after, elapsed)) = &times {
+            self.collect_times(&mut metrics, before, after, *elapsed);
+        }
+
         Ok(MetricData {
             timestamp: std::time::SystemTime::now(),
             metrics,
@@ -55,4 +276,255 @@ impl MetricCollector for CpuCollector {
     fn name(&self) -> &'static str {
         "cpu"
     }
+
+    fn prime(&self) -> bool {
+        let stat = self.stat.prime(read_proc_stat);
+        let cgroup = self.cgroup_limits
+            && current_cgroup()
+                .is_some_and(|dir| self.cgroup_usage.prime(|| read_cgroup_usage(&dir)));
+        stat || cgroup
+    }
+}
+
+/// Ticks spent in each of `CPU_STATES` between two `/proc/stat` readings.
+fn state_ticks(before: &[u64; 10], after: &[u64; 10]) -> [u64; 9] {
+    let mut delta = [0u64; 10];
+    for (d, (then, now)) in delta.iter_mut().zip(before.iter().zip(after)) {
+        *d = now.saturating_sub(*then);
+    }
+
+    // The kernel already counts guest time inside user (and guest_nice inside nice), so
+    // it is split out of those and left out of the total to avoid counting it twice.
+    let [user, nice, system, idle, iowait, irq, softirq, steal, guest, guest_nice] = delta;
+    [
+        user.saturating_sub(guest),
+        nice.saturating_sub(guest_nice),
+        system,
+        idle,
+        iowait,
+        irq,
+        softirq,
+        steal,
+        guest + guest_nice,
+    ]
+}
+
+/// Share of the interval the CPU was busy: everything except idle, waiting on IO and
+/// time stolen by the hypervisor.
+fn busy_percent(shares: &[u64; 9]) -> f64 {
+    let [_, _, _, idle, iowait, _, _, steal, _] = *shares;
+    let total: u64 = shares.iter().sum();
+    if total == 0 {
+        return 0.0;
+    }
+    100.0 - (idle + iowait + steal) as f64 / total as f64 * 100.0
+}
+
+/// Publishes `<prefix><state>_percent` for each CPU state over the sampled interval.
+fn insert_breakdown(metrics: &mut HashMap<String, MetricValue>, prefix: &str, shares: &[u64; 9]) {
+    let total: u64 = shares.iter().sum();
+
+    for (state, &ticks) in CPU_STATES.iter().zip(shares) {
+        let percent = if total > 0 {
+            ticks as f64 / total as f64 * 100.0
+        } else {
+            0.0
+        };
+        metrics.insert(
+            format!("{}{}_percent", prefix, state),
+            MetricValue::Float(percent),
+        );
+    }
+}
+
+fn read_topology(logical_cores: usize) -> CpuTopology {
+    let cpu_dir = |i: usize| Path::new(CPU_SYSFS_DIR).join(format!("cpu{}", i));
+    let read = |path: &Path| {
+        std::fs::read_to_string(path)
+            .ok()
+            .map(|s| s.trim().to_string())
+    };
+
+    let packages: BTreeSet<String> = (0..logical_cores)
+        .filter_map(|i| read(&cpu_dir(i).join("topology/physical_package_id")))
+        .collect();
+
+    // Caches shared between cores show up under each of them; count each instance once
+    // by keying on the set of CPUs that share it.
+    let mut instances = BTreeSet::new();
+    let mut cache_bytes = BTreeMap::new();
+    for i in 0..logical_cores {
+        let Ok(entries) = std::fs::read_dir(cpu_dir(i).join("cache")) else {
+            continue;
+        };
+        for entry in entries
+            .flatten()
+            .filter(|e| e.file_name().to_string_lossy().starts_with("index"))
+        {
+            let dir = entry.path();
+            let (Some(level), Some(kind), Some(size)) = (
+                read(&dir.join("level")),
+                read(&dir.join("type")),
+                read(&dir.join("size")).and_then(|s| parse_cache_size(&s)),
+            ) else {
+                continue;
+            };
+            let name = match kind.as_str() {
+                "Data" => format!("l{}d", level),
+                "Instruction" => format!("l{}i", level),
+                _ => format!("l{}", level),
+            };
+            let shared = read(&dir.join("shared_cpu_list")).unwrap_or_else(|| i.to_string());
+            if instances.insert((name.clone(), shared)) {
+                *cache_bytes.entry(name).or_insert(0) += size;
+            }
+        }
+    }
+
+    let frequency_limits = (0..logical_cores)
+        .map(|i| {
+            let khz = |attr: &str| {
+                read(&cpu_dir(i).join("cpufreq").join(attr))?
+                    .parse::<u64>()
+                    .ok()
+            };
+            Some((
+                khz("cpuinfo_min_freq")? / 1000,
+                khz("cpuinfo_max_freq")? / 1000,
+            ))
+        })
+        .collect();
+
+    CpuTopology {
+        physical_cores: System::physical_core_count(),
+        sockets: (!packages.is_empty()).then_some(packages.len()),
+        cache_bytes,
+        frequency_limits,
+        flags: read_cpu_flags(),
+    }
+}
+
+/// Parses sysfs cache sizes such as `48K` or `2048K`.
+fn parse_cache_size(size: &str) -> Option<u64> {
+    let (digits, multiplier) = match size.chars().last()? {
+        'K' => (&size[..size.len() - 1], 1024),
+        'M' => (&size[..size.len() - 1], 1024 * 1024),
+        _ => (size, 1),
+    };
+    digits.parse::<u64>().ok().map(|n| n * multiplier)
+}
+
+/// Notable flags from the first processor entry in /proc/cpuinfo (`Features` on ARM).
+fn read_cpu_flags() -> Vec<String> {
+    let Ok(cpuinfo) = std::fs::read_to_string("/proc/cpuinfo") else {
+        return Vec::new();
+    };
+    let flags: BTreeSet<&str> = cpuinfo
+        .lines()
+        .find_map(|line| {
+            let (key, value) = line.split_once(':')?;
+            matches!(key.trim(), "flags" | "Features").then_some(value)
+        })
+        .map(|value| value.split_whitespace().collect())
+        .unwrap_or_default();
+
+    NOTABLE_FLAGS
+        .iter()
+        .filter(|flag| flags.contains(*flag))
+        .map(|flag| flag.to_string())
+        .collect()
+}
+
+/// Share of a `quota_cores` CPU quota used between two `usage_usec` readings.
+fn quota_usage_percent(quota_cores: f64, before_us: u64, after_us: u64, elapsed: f64) -> f64 {
+    let cores = rate(before_us, after_us, elapsed) / 1e6;
+    (cores / quota_cores * 100.0).min(100.0)
+}
+
+/// Cumulative CPU time of a cgroup in microseconds.
+fn read_cgroup_usage(dir: &Path) -> Result<u64, ()> {
+    read_keyed(dir, "cpu.stat")
+        .and_then(|stat| stat.get("usage_usec").copied())
+        .ok_or(())
+}
+
+fn read_proc_stat() -> std::io::Result<ProcStat> {
+    let contents = std::fs::read_to_string("/proc/stat")?;
+    let mut stat = ProcStat::default();
+
+    for line in contents.lines() {
+        let mut fields = line.split_whitespace();
+        let Some(label) = fields.next() else {
+            continue;
+        };
+        let mut numbers = fields.map(|f| f.parse::<u64>().unwrap_or(0));
+
+        if label.starts_with("cpu") {
+            // Older kernels have fewer columns; missing ones stay zero.
+            let mut times = [0u64; 10];
+            for (slot, value) in times.iter_mut().zip(numbers.by_ref()) {
+                *slot = value;
+            }
+            if label == "cpu" {
+                stat.total = times;
+            } else {
+                stat.cores.push(times);
+            }
+        } else if label == "ctxt" {
+            stat.context_switches = numbers.next().unwrap_or(0);
+        } else if label == "intr" {
+            stat.interrupts = numbers.next().unwrap_or(0);
+        }
+    }
+
+    Ok(stat)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cgroup_usage_is_a_share_of_the_quota() {
+        // 1.5 s of CPU time in one second against a 2-core quota.
+        assert_eq!(quota_usage_percent(2.0, 10_000_000, 11_500_000, 1.0), 75.0);
+        // Half a core over two seconds against a quarter-core quota is capped.
+        assert_eq!(quota_usage_percent(0.25, 0, 1_000_000, 2.0), 100.0);
+        assert_eq!(quota_usage_percent(1.0, 5_000, 5_000, 1.0), 0.0);
+    }
+
+    #[test]
+    fn busy_share_leaves_out_idle_iowait_and_steal() {
+        // user nice system idle iowait irq softirq steal guest guest_nice
+        let before = [100, 0, 50, 800, 20, 0, 0, 10, 0, 0];
+        let after = [160, 0, 70, 900, 30, 5, 5, 20, 0, 0];
+        let shares = state_ticks(&before, &after);
+        assert_eq!(shares, [60, 0, 20, 100, 10, 5, 5, 10, 0]);
+        assert_eq!(busy_percent(&shares), 100.0 - 120.0 / 210.0 * 100.0);
+    }
+
+    #[test]
+    fn guest_time_is_not_counted_twice() {
+        let before = [0; 10];
+        let after = [80, 10, 0, 100, 0, 0, 0, 0, 30, 10];
+        let shares = state_ticks(&before, &after);
+        assert_eq!(shares, [50, 0, 0, 100, 0, 0, 0, 0, 40]);
+        assert_eq!(busy_percent(&shares), 100.0 - 100.0 / 190.0 * 100.0);
+    }
+
+    #[test]
+    fn idle_interval_is_zero_percent() {
+        assert_eq!(busy_percent(&state_ticks(&[5; 10], &[5; 10])), 0.0);
+    }
+
+    #[test]
+    fn parses_sysfs_cache_sizes() {
+        assert_eq!(parse_cache_size("32K"), Some(32 * 1024));
+        assert_eq!(parse_cache_size("8M"), Some(8 * 1024 * 1024));
+        assert_eq!(parse_cache_size("512"), Some(512));
+        assert_eq!(parse_cache_size(""), None);
+        assert_eq!(parse_cache_size("K"), None);
+        assert_eq!(parse_cache_size("1G"), None);
+        assert_eq!(parse_cache_size("-4K"), None);
+    }
 }
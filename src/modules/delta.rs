@@ -0,0 +1,127 @@
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Keeps the previous reading of a cumulative source (e.g. `/proc/diskstats`) so rates
+/// can be computed between samples.
+///
+/// The engine primes every sampler before the first sample and waits
+/// [`INITIAL_INTERVAL`] once for all of them. A sampler that was never primed has nothing
+/// to compare against on its first call, so it takes two readings that interval apart
+/// itself. Later calls measure against the reading from the previous sample.
+pub(crate) struct DeltaSampler<T> {
+    previous: Mutex<Option<(Instant, T)>>,
+}
+
+pub(crate) const INITIAL_INTERVAL: Duration = Duration::from_millis(200);
+
+impl<T: Clone> DeltaSampler<T> {
+    pub(crate) fn new() -> Self {
+        Self {
+            previous: Mutex::new(None),
+        }
+    }
+
+    /// Stores a first reading for the next `sample` to measure against. Returns false if
+    /// there already was one or the read failed.
+    pub(crate) fn prime<E>(&self, read: impl Fn() -> Result<T, E>) -> bool {
+        let mut previous = self.previous.lock().unwrap_or_else(|e| e.into_inner());
+        if previous.is_some() {
+            return false;
+        }
+        match read() {
+            Ok(reading) => {
+                *previous = Some((Instant::now(), reading));
+                true
+            }
+            Err(_) => false,
+        }
+    }
+
+    /// Returns the previous reading, the current one and the seconds between them.
+    pub(crate) fn sample<E>(&self, read: impl Fn() -> Result<T, E>) -> Result<(T, T, f64), E> {
+        let mut previous = self.previous.lock().unwrap_or_else(|e| e.into_inner());
+
+        let (before_at, before) = match previous.take() {
+            Some(reading) => reading,
+            None => {
+                let first = read()?;
+                let at = Instant::now();
+                std::thread::sleep(INITIAL_INTERVAL);
+                (at, first)
+            }
+        };
+
+        let current = read()?;
+        let now = Instant::now();
+        *previous = Some((now, current.clone()));
+
+        let elapsed = now
+            .duration_since(before_at)
+            .as_secs_f64()
+            .max(f64::EPSILON);
+        Ok((before, current, elapsed))
+    }
+}
+
+impl<T: Clone> Default for DeltaSampler<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Per-second rate of a counter, treating a counter that went backwards (reset or
+/// wrap) as zero rather than a huge negative spike.
+pub(crate) fn rate(before: u64, after: u64, elapsed_secs: f64) -> f64 {
+    after.saturating_sub(before) as f64 / elapsed_secs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    fn counter(next: &Cell<u64>) -> impl Fn() -> Result<u64, ()> + '_ {
+        move || {
+            next.set(next.get() + 1);
+            Ok(next.get())
+        }
+    }
+
+    #[test]
+    fn primed_sampler_measures_from_the_primed_reading() {
+        let next = Cell::new(0);
+        let sampler = DeltaSampler::new();
+        assert!(sampler.prime(counter(&next)));
+        assert!(!sampler.prime(counter(&next)));
+
+        let started = Instant::now();
+        let (before, after, _) = sampler.sample(counter(&next)).unwrap();
+        assert_eq!((before, after), (1, 2));
+        assert!(started.elapsed() < INITIAL_INTERVAL);
+
+        let (before, after, _) = sampler.sample(counter(&next)).unwrap();
+        assert_eq!((before, after), (2, 3));
+    }
+
+    #[test]
+    fn unprimed_sampler_waits_on_its_first_call() {
+        let next = Cell::new(0);
+        let sampler = DeltaSampler::new();
+        let (before, after, elapsed) = sampler.sample(counter(&next)).unwrap();
+        assert_eq!((before, after), (1, 2));
+        assert!(elapsed >= INITIAL_INTERVAL.as_secs_f64());
+    }
+
+    #[test]
+    fn failed_prime_leaves_the_sampler_unprimed() {
+        let sampler: DeltaSampler<u64> = DeltaSampler::new();
+        assert!(!sampler.prime(|| Err::<u64, ()>(())));
+        assert!(sampler.prime(|| Ok::<u64, ()>(7)));
+    }
+
+    #[test]
+    fn counter_resets_are_not_negative_rates() {
+        assert_eq!(rate(100, 300, 2.0), 100.0);
+        assert_eq!(rate(300, 100, 2.0), 0.0);
+    }
+}
@@ -1,12 +1,94 @@
 use sysinfo::Disks;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use crate::config::{glob_match, DiskConfig};
 use crate::core::{MetricCollector, MetricData, MetricValue};
+use crate::modules::delta::{rate, DeltaSampler};
 
-pub struct DiskCollector;
+#[derive(Default)]
+pub struct DiskCollector {
+    config: DiskConfig,
+    io: DeltaSampler<HashMap<String, IoCounters>>,
+}
+
+/// Cumulative per-device counters from one line of `/proc/diskstats`.
+#[derive(Debug, Clone, Copy, Default)]
+struct IoCounters {
+    reads: u64,
+    sectors_read: u64,
+    read_ms: u64,
+    writes: u64,
+    sectors_written: u64,
+    write_ms: u64,
+    io_ms: u64,
+    weighted_io_ms: u64,
+}
+
+// /proc/diskstats always counts in 512-byte sectors, whatever the device's block size.
+const SECTOR_BYTES: u64 = 512;
 
 impl DiskCollector {
     pub fn new() -> Self {
-        DiskCollector
+        DiskCollector::default()
+    }
+
+    pub fn with_config(config: DiskConfig) -> Self {
+        DiskCollector {
+            config,
+            io: DeltaSampler::default(),
+        }
+    }
+
+    fn is_io_device_included(&self, device: &str) -> bool {
+        if self
+            .config
+            .io_exclude_devices
+            .iter()
+            .any(|p| glob_match(p, device))
+        {
+            return false;
+        }
+        // Whole disks have an entry in /sys/block; partitions only appear beneath them.
+        self.config.io_include_partitions
+            || !Path::new("/sys/block").exists()
+            || Path::new("/sys/block")
+                .join(device.replace('/', "!"))
+                .exists()
+    }
+
+    fn collect_io(&self, metrics: &mut HashMap<String, MetricValue>) {
+        let Ok((before, after, elapsed)) = self.io.sample(read_diskstats) else {
+            return;
+        };
+
+        let mut devices: Vec<_> = after
+            .iter()
+            .filter(|(name, _)| self.is_io_device_included(name))
+            .collect();
+        devices.sort_by_key(|(name, _)| *name);
+
+        for (name, now) in devices {
+            let then = before.get(name).copied().unwrap_or(*now);
+            insert_device_io(metrics, name, &then, now, elapsed);
+        }
+    }
+
+    fn is_included(&self, mount: &str, fs_type: &str) -> bool {
+        let config = &self.config;
+        if !config.include_fs_types.is_empty()
+            && !config.include_fs_types.iter().any(|t| t == fs_type)
+        {
+            return false;
+        }
+        if config.exclude_fs_types.iter().any(|t| t == fs_type) {
+            return false;
+        }
+        if !config.include_mounts.is_empty()
+            && !config.include_mounts.iter().any(|p| glob_match(p, mount))
+        {
+            return false;
+        }
+        !config.exclude_mounts.iter().any(|p| glob_match(p, mount))
     }
 }
 
@@ -14,14 +96,100 @@ impl MetricCollector for DiskCollector {
     fn collect(&self) -> Result<MetricData, Box<dyn std::error::Error>> {
         let disks = Disks::new_with_refreshed_list();
 
-        let mut total: u64 = 0;
-        let mut free: u64 = 0;
+        let mut metrics = HashMap::new();
+        let mut totals = SpaceTotals::default();
+        let mut mount_count: i64 = 0;
+        let mut mounts: Vec<MetricValue> = Vec::new();
 
         for disk in disks.list() {
-            total += disk.total_space();
-            free += disk.available_space();
+            let mount = disk.mount_point().to_string_lossy().to_string();
+            let device = disk.name().to_string_lossy().to_string();
+            let fs_type = disk.file_system().to_string_lossy().to_string();
+
+            if !self.is_included(&mount, &fs_type) {
+                continue;
+            }
+
+            let disk_total = disk.total_space();
+            let disk_free = disk.available_space();
+            let disk_used = disk_total.saturating_sub(disk_free);
+            let disk_percent = if disk_total > 0 {
+                (disk_used as f64 / disk_total as f64) * 100.0
+            } else {
+                0.0
+            };
+
+            let identity = filesystem_id(disk.mount_point())
+                .map(FilesystemId::Device)
+                .unwrap_or_else(|| FilesystemId::Name(device.clone()));
+            totals.add(identity, disk_total, disk_free);
+            mount_count += 1;
+
+            let prefix = format!("mount.{}", mount);
+            metrics.insert(
+                format!("{}.device", prefix),
+                MetricValue::String(device.clone()),
+            );
+            metrics.insert(
+                format!("{}.fs_type", prefix),
+                MetricValue::String(fs_type.clone()),
+            );
+            metrics.insert(
+                format!("{}.removable", prefix),
+                MetricValue::Boolean(disk.is_removable()),
+            );
+            metrics.insert(
+                format!("{}.total_bytes", prefix),
+                MetricValue::Integer(disk_total as i64),
+            );
+            metrics.insert(
+                format!("{}.used_bytes", prefix),
+                MetricValue::Integer(disk_used as i64),
+            );
+            metrics.insert(
+                format!("{}.free_bytes", prefix),
+                MetricValue::Integer(disk_free as i64),
+            );
+            metrics.insert(
+                format!("{}.usage_percent", prefix),
+                MetricValue::Float(disk_percent),
+            );
+
+            // Some filesystems (btrfs, vfat, most network mounts) report no inode limit.
+            let inodes = inode_usage(disk.mount_point()).filter(|i| i.total > 0);
+            if let Some(inodes) = inodes {
+                let inodes_used = inodes.total.saturating_sub(inodes.free);
+                let inode_percent = (inodes_used as f64 / inodes.total as f64) * 100.0;
+                metrics.insert(
+                    format!("{}.inodes", prefix),
+                    MetricValue::Integer(inodes.total as i64),
+                );
+                metrics.insert(
+                    format!("{}.inodes_used", prefix),
+                    MetricValue::Integer(inodes_used as i64),
+                );
+                metrics.insert(
+                    format!("{}.inodes_free", prefix),
+                    MetricValue::Integer(inodes.free as i64),
+                );
+                metrics.insert(
+                    format!("{}.inode_usage_percent", prefix),
+                    MetricValue::Float(inode_percent),
+                );
+
+                mounts.push(MetricValue::String(format!(
+                    "{} ({} on {}): {:.1}%, inodes {:.1}%",
+                    mount, fs_type, device, disk_percent, inode_percent
+                )));
+            } else {
+                mounts.push(MetricValue::String(format!(
+                    "{} ({} on {}): {:.1}%",
+                    mount, fs_type, device, disk_percent
+                )));
+            }
         }
 
+        let SpaceTotals { total, free, .. } = totals;
         let used = total.saturating_sub(free);
         let usage_percent = if total > 0 {
             (used as f64 / total as f64) * 100.0
@@ -29,12 +197,14 @@ impl MetricCollector for DiskCollector {
             0.0
         };
 
-        let mut metrics = HashMap::new();
         metrics.insert("total_bytes".to_string(), MetricValue::Integer(total as i64));
         metrics.insert("used_bytes".to_string(), MetricValue::Integer(used as i64));
         metrics.insert("free_bytes".to_string(), MetricValue::Integer(free as i64));
         metrics.insert("usage_percent".to_string(), MetricValue::Float(usage_percent));
-        metrics.insert("disk_count".to_string(), MetricValue::Integer(disks.list().len() as i64));
+        metrics.insert("disk_count".to_string(), MetricValue::Integer(mount_count));
+        metrics.insert("mounts".to_string(), MetricValue::List(mounts));
+
+        self.collect_io(&mut metrics);
 
         Ok(MetricData {
             timestamp: std::time::SystemTime::now(),
@@ -45,4 +215,329 @@ impl MetricCollector for DiskCollector {
     fn name(&self) -> &'static str {
         "disk"
     }
+
+    fn prime(&self) -> bool {
+        self.io.prime(read_diskstats)
+    }
+}
+
+#[derive(PartialEq, Eq, Hash)]
+enum FilesystemId {
+    Device(u64),
+    Name(String),
+}
+
+/// Aggregate space across mounts.
+///
+/// A filesystem mounted in several places (bind mounts) is only counted once towards
+/// the aggregate. Device names are not unique enough for this: every tmpfs or network
+/// share may report the same one.
+#[derive(Default)]
+struct SpaceTotals {
+    counted: HashSet<FilesystemId>,
+    total: u64,
+    free: u64,
+}
+
+impl SpaceTotals {
+    fn add(&mut self, identity: FilesystemId, total: u64, free: u64) {
+        if self.counted.insert(identity) {
+            self.total += total;
+            self.free += free;
+        }
+    }
+}
+
+/// The `st_dev` of a mount point, shared by every mount of the same filesystem.
+#[cfg(unix)]
+fn filesystem_id(mount: &Path) -> Option<u64> {
+    use std::os::unix::fs::MetadataExt;
+
+    std::fs::metadata(mount).ok().map(|m| m.dev())
+}
+
+#[cfg(not(unix))]
+fn filesystem_id(_mount: &Path) -> Option<u64> {
+    None
+}
+
+struct InodeUsage {
+    total: u64,
+    free: u64,
+}
+
+#[cfg(unix)]
+fn inode_usage(mount: &Path) -> Option<InodeUsage> {
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+
+    let path = CString::new(mount.as_os_str().as_bytes()).ok()?;
+    let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+    // SAFETY: `path` is NUL-terminated and `stat` is a valid, writable statvfs.
+    if unsafe { libc::statvfs(path.as_ptr(), &mut stat) } != 0 {
+        return None;
+    }
+
+    Some(InodeUsage {
+        total: stat.f_files as u64,
+        free: stat.f_ffree as u64,
+    })
+}
+
+#[cfg(not(unix))]
+fn inode_usage(_mount: &Path) -> Option<InodeUsage> {
+    None
+}
+
+/// Per-device rates from two `/proc/diskstats` snapshots `elapsed` seconds apart.
+fn insert_device_io(
+    metrics: &mut HashMap<String, MetricValue>,
+    name: &str,
+    then: &IoCounters,
+    now: &IoCounters,
+    elapsed: f64,
+) {
+    let ios = (now.reads + now.writes).saturating_sub(then.reads + then.writes);
+    let io_ms = (now.read_ms + now.write_ms).saturating_sub(then.read_ms + then.write_ms);
+    let await_ms = if ios > 0 {
+        io_ms as f64 / ios as f64
+    } else {
+        0.0
+    };
+    let elapsed_ms = elapsed * 1000.0;
+
+    let prefix = format!("device.{}", name);
+    metrics.insert(
+        format!("{}.read_bytes_total", prefix),
+        MetricValue::Integer((now.sectors_read * SECTOR_BYTES) as i64),
+    );
+    metrics.insert(
+        format!("{}.write_bytes_total", prefix),
+        MetricValue::Integer((now.sectors_written * SECTOR_BYTES) as i64),
+    );
+    metrics.insert(
+        format!("{}.read_bytes_per_sec", prefix),
+        MetricValue::Float(
+            rate(then.sectors_read, now.sectors_read, elapsed) * SECTOR_BYTES as f64,
+        ),
+    );
+    metrics.insert(
+        format!("{}.write_bytes_per_sec", prefix),
+        MetricValue::Float(
+            rate(then.sectors_written, now.sectors_written, elapsed) * SECTOR_BYTES as f64,
+        ),
+    );
+    metrics.insert(
+        format!("{}.read_iops", prefix),
+        MetricValue::Float(rate(then.reads, now.reads, elapsed)),
+    );
+    metrics.insert(
+        format!("{}.write_iops", prefix),
+        MetricValue::Float(rate(then.writes, now.writes, elapsed)),
+    );
+    metrics.insert(format!("{}.await_ms", prefix), MetricValue::Float(await_ms));
+    metrics.insert(
+        format!("{}.avg_queue_depth", prefix),
+        MetricValue::Float(
+            now.weighted_io_ms.saturating_sub(then.weighted_io_ms) as f64 / elapsed_ms,
+        ),
+    );
+    metrics.insert(
+        format!("{}.utilization_percent", prefix),
+        MetricValue::Float(
+            (now.io_ms.saturating_sub(then.io_ms) as f64 / elapsed_ms * 100.0).min(100.0),
+        ),
+    );
+}
+
+fn read_diskstats() -> std::io::Result<HashMap<String, IoCounters>> {
+    std::fs::read_to_string("/proc/diskstats").map(|contents| parse_diskstats(&contents))
+}
+
+fn parse_diskstats(contents: &str) -> HashMap<String, IoCounters> {
+    let mut devices = HashMap::new();
+
+    for line in contents.lines() {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if fields.len() < 14 {
+            continue;
+        }
+        let field = |i: usize| fields[i].parse::<u64>().unwrap_or(0);
+
+        devices.insert(
+            fields[2].to_string(),
+            IoCounters {
+                reads: field(3),
+                sectors_read: field(5),
+                read_ms: field(6),
+                writes: field(7),
+                sectors_written: field(9),
+                write_ms: field(10),
+                io_ms: field(12),
+                weighted_io_ms: field(13),
+            },
+        );
+    }
+
+    devices
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn collector(config: DiskConfig) -> DiskCollector {
+        DiskCollector::with_config(config)
+    }
+
+    #[test]
+    fn includes_by_mount_glob() {
+        let disk = collector(DiskConfig {
+            include_mounts: vec!["/".to_string(), "/home*".to_string()],
+            exclude_mounts: vec!["/home/scratch".to_string()],
+            ..DiskConfig::default()
+        });
+        assert!(disk.is_included("/", "ext4"));
+        assert!(disk.is_included("/home/alice", "ext4"));
+        assert!(!disk.is_included("/home/scratch", "ext4"));
+        assert!(!disk.is_included("/var", "ext4"));
+    }
+
+    #[test]
+    fn includes_by_fs_type() {
+        let disk = collector(DiskConfig {
+            include_fs_types: vec!["ext4".to_string(), "xfs".to_string()],
+            exclude_fs_types: vec!["xfs".to_string()],
+            include_mounts: Vec::new(),
+            exclude_mounts: Vec::new(),
+            ..DiskConfig::default()
+        });
+        assert!(disk.is_included("/", "ext4"));
+        assert!(!disk.is_included("/data", "xfs"));
+        assert!(!disk.is_included("/run", "tmpfs"));
+
+        let disk = collector(DiskConfig {
+            include_fs_types: Vec::new(),
+            exclude_fs_types: vec!["tmpfs".to_string()],
+            include_mounts: Vec::new(),
+            exclude_mounts: Vec::new(),
+            ..DiskConfig::default()
+        });
+        assert!(disk.is_included("/boot/efi", "vfat"));
+        assert!(!disk.is_included("/run", "tmpfs"));
+    }
+
+    #[test]
+    fn counts_a_filesystem_mounted_twice_once() {
+        let mut totals = SpaceTotals::default();
+        totals.add(FilesystemId::Device(2049), 100, 40);
+        totals.add(FilesystemId::Device(2049), 100, 40);
+        totals.add(FilesystemId::Device(2050), 50, 10);
+        // Without a device id, mounts fall back to their name.
+        totals.add(FilesystemId::Name("server:/export".to_string()), 30, 5);
+        totals.add(FilesystemId::Name("server:/export".to_string()), 30, 5);
+        assert_eq!((totals.total, totals.free), (180, 55));
+    }
+
+    const DISKSTATS: &str = "\
+   7       0 loop0 12 0 34 5 0 0 0 0 0 8 5 0 0 0 0 0 0
+ 259       0 nvme0n1 183406 61311 14402536 52718 512204 312480 29364416 812402 0 404120 892345 0 0 0 0 34511 27224
+ 259       1 nvme0n1p1 314 1036 14210 81 2 0 2 0 0 92 81 0 0 0 0 0 0
+   8      16 sdb 90 0 4144 41 0 0 0 0 0 52 41
+";
+
+    fn float(metrics: &HashMap<String, MetricValue>, key: &str) -> f64 {
+        match metrics.get(key) {
+            Some(MetricValue::Float(v)) => *v,
+            other => panic!("{key}: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn parses_diskstats() {
+        let devices = parse_diskstats(DISKSTATS);
+        // Pre-4.18 kernels print 14 fields and are still read; short lines are skipped.
+        assert_eq!(devices.len(), 4);
+        let nvme = devices["nvme0n1"];
+        assert_eq!(nvme.reads, 183406);
+        assert_eq!(nvme.sectors_read, 14402536);
+        assert_eq!(nvme.read_ms, 52718);
+        assert_eq!(nvme.writes, 512204);
+        assert_eq!(nvme.sectors_written, 29364416);
+        assert_eq!(nvme.write_ms, 812402);
+        assert_eq!(nvme.io_ms, 404120);
+        assert_eq!(nvme.weighted_io_ms, 892345);
+        assert_eq!(devices["sdb"].weighted_io_ms, 41);
+        assert!(parse_diskstats("   8 0 sda 1 2 3\n").is_empty());
+    }
+
+    #[test]
+    fn derives_rates_from_two_snapshots() {
+        let then = IoCounters {
+            reads: 1000,
+            sectors_read: 8000,
+            read_ms: 500,
+            writes: 2000,
+            sectors_written: 16000,
+            write_ms: 1500,
+            io_ms: 10_000,
+            weighted_io_ms: 20_000,
+        };
+        // Two seconds later: 100 reads and 300 writes that took 1200 ms between them.
+        let now = IoCounters {
+            reads: 1100,
+            sectors_read: 8800,
+            read_ms: 700,
+            writes: 2300,
+            sectors_written: 18400,
+            write_ms: 2500,
+            io_ms: 11_000,
+            weighted_io_ms: 23_000,
+        };
+        let mut metrics = HashMap::new();
+        insert_device_io(&mut metrics, "sda", &then, &now, 2.0);
+
+        assert_eq!(float(&metrics, "device.sda.read_iops"), 50.0);
+        assert_eq!(float(&metrics, "device.sda.write_iops"), 150.0);
+        assert_eq!(
+            float(&metrics, "device.sda.read_bytes_per_sec"),
+            400.0 * 512.0
+        );
+        assert_eq!(
+            float(&metrics, "device.sda.write_bytes_per_sec"),
+            1200.0 * 512.0
+        );
+        assert_eq!(float(&metrics, "device.sda.await_ms"), 3.0);
+        assert_eq!(float(&metrics, "device.sda.avg_queue_depth"), 1.5);
+        assert_eq!(float(&metrics, "device.sda.utilization_percent"), 50.0);
+        assert!(matches!(
+            metrics["device.sda.write_bytes_total"],
+            MetricValue::Integer(v) if v == 18400 * 512
+        ));
+    }
+
+    #[test]
+    fn idle_device_has_no_await_and_busy_time_is_capped() {
+        let then = IoCounters::default();
+        let now = IoCounters {
+            io_ms: 2500,
+            ..IoCounters::default()
+        };
+        let mut metrics = HashMap::new();
+        insert_device_io(&mut metrics, "sda", &then, &now, 2.0);
+        assert_eq!(float(&metrics, "device.sda.await_ms"), 0.0);
+        assert_eq!(float(&metrics, "device.sda.utilization_percent"), 100.0);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn paths_on_one_filesystem_share_an_id() {
+        let dir = std::env::temp_dir();
+        let nested = dir.join(format!("gim-disk-{}", std::process::id()));
+        std::fs::create_dir_all(&nested).unwrap();
+        let ids = (filesystem_id(&dir), filesystem_id(&nested));
+        std::fs::remove_dir(&nested).unwrap();
+        assert!(ids.0.is_some());
+        assert_eq!(ids.0, ids.1);
+    }
 }
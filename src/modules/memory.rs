@@ -1,12 +1,91 @@
 use crate::core::{MetricCollector, MetricData, MetricValue};
+use crate::modules::cgroupfs::{current_cgroup, read_keyed, read_limit};
+use crate::modules::delta::{rate, DeltaSampler};
 use std::collections::HashMap;
 use sysinfo::{System, RefreshKind};
 
-pub struct MemoryCollector {}
+// /proc/meminfo fields (reported in kB) published as `<key>` in bytes.
+const MEMINFO_FIELDS: &[(&str, &str)] = &[
+    ("Buffers", "buffers_bytes"),
+    ("Cached", "cached_bytes"),
+    ("Shmem", "shared_bytes"),
+    ("Slab", "slab_bytes"),
+    ("SReclaimable", "slab_reclaimable_bytes"),
+    ("SUnreclaim", "slab_unreclaimable_bytes"),
+    ("Dirty", "dirty_bytes"),
+    ("Writeback", "writeback_bytes"),
+    ("Mapped", "mapped_bytes"),
+    ("PageTables", "page_tables_bytes"),
+    ("Committed_AS", "committed_bytes"),
+    ("CommitLimit", "commit_limit_bytes"),
+    ("AnonHugePages", "anon_huge_pages_bytes"),
+    ("Hugepagesize", "hugepage_size_bytes"),
+];
+
+// Paging activity from /proc/vmstat, published as `paging.<key>_total` and
+// `paging.<key>_per_sec`. Each key sums the fields with the given prefixes, since
+// scan/steal counters are split by reclaim path (and by zone on older kernels).
+const VMSTAT_COUNTERS: &[(&str, &[&str])] = &[
+    ("page_faults", &["pgfault"]),
+    ("major_faults", &["pgmajfault"]),
+    ("swap_in_pages", &["pswpin"]),
+    ("swap_out_pages", &["pswpout"]),
+    ("pages_scanned", &["pgscan_kswapd", "pgscan_direct"]),
+    ("pages_stolen", &["pgsteal_kswapd", "pgsteal_direct"]),
+    ("oom_kills", &["oom_kill"]),
+    ("thp_fault_allocs", &["thp_fault_alloc"]),
+    ("thp_fault_fallbacks", &["thp_fault_fallback"]),
+    ("thp_collapse_allocs", &["thp_collapse_alloc"]),
+    ("thp_splits", &["thp_split_page"]),
+];
+
+#[derive(Default)]
+pub struct MemoryCollector {
+    cgroup_limits: bool,
+    vmstat: DeltaSampler<HashMap<String, u64>>,
+}
 
 impl MemoryCollector {
     pub fn new() -> Self {
-        MemoryCollector {}
+        MemoryCollector::default()
+    }
+
+    /// Reports totals and usage against the cgroup's `memory.max` when one is set, so a
+    /// container sees its own limit rather than the host's RAM.
+    pub fn with_cgroup_limits(cgroup_limits: bool) -> Self {
+        MemoryCollector {
+            cgroup_limits,
+            ..Self::default()
+        }
+    }
+
+    fn collect_paging(&self, metrics: &mut HashMap<String, MetricValue>) {
+        let Ok((before, after, elapsed)) = self.vmstat.sample(read_vmstat) else {
+            return;
+        };
+
+        for (key, fields) in VMSTAT_COUNTERS {
+            let sum = |vmstat: &HashMap<String, u64>| -> Option<u64> {
+                let mut matched = vmstat
+                    .iter()
+                    .filter(|(name, _)| fields.iter().any(|f| is_vmstat_field(name, f)))
+                    .peekable();
+                matched.peek()?;
+                Some(matched.map(|(_, value)| value).sum())
+            };
+            let Some(now) = sum(&after) else {
+                continue;
+            };
+            let then = sum(&before).unwrap_or(now);
+            metrics.insert(
+                format!("paging.{}_total", key),
+                MetricValue::Integer(now as i64),
+            );
+            metrics.insert(
+                format!("paging.{}_per_sec", key),
+                MetricValue::Float(rate(then, now, elapsed)),
+            );
+        }
     }
 }
 
@@ -33,6 +112,16 @@ impl MetricCollector for MemoryCollector {
         };
         metrics.insert("memory_usage_percent".to_string(), MetricValue::from(memory_percent));
 
+        if self.cgroup_limits {
+            apply_cgroup_limit(sys.total_memory(), &mut metrics);
+        }
+
+        // The detailed breakdown is Linux-only; elsewhere only the sysinfo figures are published.
+        if let Ok(meminfo) = read_meminfo() {
+            insert_meminfo(&meminfo, &mut metrics);
+        }
+        self.collect_paging(&mut metrics);
+
         Ok(MetricData {
             timestamp: std::time::SystemTime::now(),
             metrics,
@@ -42,4 +131,235 @@ impl MetricCollector for MemoryCollector {
     fn name(&self) -> &'static str {
         "memory"
     }
+
+    fn prime(&self) -> bool {
+        self.vmstat.prime(read_vmstat)
+    }
+}
+
+fn apply_cgroup_limit(host_total: u64, metrics: &mut HashMap<String, MetricValue>) {
+    let Some(dir) = current_cgroup() else {
+        return;
+    };
+    let (Some(max), Some(current)) = (
+        read_limit(&dir, "memory.max"),
+        read_limit(&dir, "memory.current"),
+    ) else {
+        return;
+    };
+
+    let inactive_file = read_keyed(&dir, "memory.stat")
+        .and_then(|stat| stat.get("inactive_file").copied())
+        .unwrap_or(0);
+    let (total, used) = cgroup_working_set(host_total, max, current, inactive_file);
+    let free = total - used;
+    let percent = if total > 0 {
+        used as f64 / total as f64 * 100.0
+    } else {
+        0.0
+    };
+
+    metrics.insert(
+        "total_memory_bytes".to_string(),
+        MetricValue::from(total as i64),
+    );
+    metrics.insert(
+        "used_memory_bytes".to_string(),
+        MetricValue::from(used as i64),
+    );
+    metrics.insert(
+        "free_memory_bytes".to_string(),
+        MetricValue::from(free as i64),
+    );
+    metrics.insert(
+        "available_memory_bytes".to_string(),
+        MetricValue::from(free as i64),
+    );
+    metrics.insert(
+        "memory_usage_percent".to_string(),
+        MetricValue::from(percent),
+    );
+    metrics.insert("limited_by_cgroup".to_string(), MetricValue::Boolean(true));
+}
+
+/// Memory available to and used by a cgroup, as `(total, used)`.
+///
+/// Like `docker stats`, this counts the working set: inactive page cache can be reclaimed
+/// before the limit is hit.
+fn cgroup_working_set(host_total: u64, max: u64, current: u64, inactive_file: u64) -> (u64, u64) {
+    let total = max.min(host_total);
+    let used = current.saturating_sub(inactive_file).min(total);
+    (total, used)
+}
+
+fn insert_meminfo(meminfo: &HashMap<String, u64>, metrics: &mut HashMap<String, MetricValue>) {
+    for (field, key) in MEMINFO_FIELDS {
+        if let Some(kb) = meminfo.get(*field) {
+            metrics.insert(key.to_string(), MetricValue::Integer((kb * 1024) as i64));
+        }
+    }
+
+    if let (Some(committed), Some(limit)) =
+        (meminfo.get("Committed_AS"), meminfo.get("CommitLimit"))
+    {
+        if *limit > 0 {
+            let percent = *committed as f64 / *limit as f64 * 100.0;
+            metrics.insert("committed_percent".to_string(), MetricValue::Float(percent));
+        }
+    }
+
+    // Hugepage totals are page counts rather than kB. The pool size is a gauge, so it
+    // avoids the `_total` suffix that marks counters.
+    if let Some(total) = meminfo.get("HugePages_Total") {
+        metrics.insert(
+            "hugepages_count".to_string(),
+            MetricValue::Integer(*total as i64),
+        );
+    }
+    if let Some(free) = meminfo.get("HugePages_Free") {
+        metrics.insert(
+            "hugepages_free".to_string(),
+            MetricValue::Integer(*free as i64),
+        );
+    }
+}
+
+fn read_meminfo() -> std::io::Result<HashMap<String, u64>> {
+    std::fs::read_to_string("/proc/meminfo").map(|contents| parse_meminfo(&contents))
+}
+
+/// Parses `Field:   1234 kB` lines; values are left in the file's units.
+fn parse_meminfo(contents: &str) -> HashMap<String, u64> {
+    contents
+        .lines()
+        .filter_map(|line| {
+            let (field, rest) = line.split_once(':')?;
+            let value = rest.split_whitespace().next()?.parse().ok()?;
+            Some((field.to_string(), value))
+        })
+        .collect()
+}
+
+/// `pgscan_kswapd` matches itself and the per-zone `pgscan_kswapd_normal` etc. of older
+/// kernels, but not unrelated fields sharing the prefix such as `pgscan_direct_throttle`.
+fn is_vmstat_field(name: &str, field: &str) -> bool {
+    matches!(
+        name.strip_prefix(field),
+        Some("" | "_dma" | "_dma32" | "_normal" | "_high" | "_movable")
+    )
+}
+
+fn read_vmstat() -> std::io::Result<HashMap<String, u64>> {
+    let contents = std::fs::read_to_string("/proc/vmstat")?;
+
+    Ok(contents
+        .lines()
+        .filter_map(|line| {
+            let (name, value) = line.split_once(' ')?;
+            Some((name.to_string(), value.trim().parse().ok()?))
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MEMINFO: &str = "\
+MemTotal:       16303428 kB
+MemFree:         1186108 kB
+Buffers:          412040 kB
+Cached:          6790180 kB
+Committed_AS:   12582912 kB
+CommitLimit:    16777216 kB
+HugePages_Total:       8
+HugePages_Free:        6
+Hugepagesize:       2048 kB
+";
+
+    fn integer(metrics: &HashMap<String, MetricValue>, key: &str) -> i64 {
+        match metrics.get(key) {
+            Some(MetricValue::Integer(v)) => *v,
+            other => panic!("{key}: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn working_set_leaves_out_inactive_cache() {
+        const GIB: u64 = 1 << 30;
+        // 512 MiB of the 1.5 GiB charged to a 2 GiB cgroup is inactive page cache.
+        assert_eq!(
+            cgroup_working_set(16 * GIB, 2 * GIB, 3 * GIB / 2, GIB / 2),
+            (2 * GIB, GIB)
+        );
+        // A limit above the host's RAM is no limit at all.
+        assert_eq!(
+            cgroup_working_set(16 * GIB, 64 * GIB, GIB, 0),
+            (16 * GIB, GIB)
+        );
+        // Usage is clamped to the limit, and cache larger than usage leaves nothing.
+        assert_eq!(cgroup_working_set(16 * GIB, GIB, 2 * GIB, 0), (GIB, GIB));
+        assert_eq!(
+            cgroup_working_set(16 * GIB, GIB, GIB / 4, GIB / 2),
+            (GIB, 0)
+        );
+    }
+
+    #[test]
+    fn parses_meminfo_lines() {
+        let meminfo = parse_meminfo(MEMINFO);
+        assert_eq!(meminfo["MemTotal"], 16303428);
+        assert_eq!(meminfo["HugePages_Total"], 8);
+        assert_eq!(meminfo["Hugepagesize"], 2048);
+        assert!(parse_meminfo("garbage\nMemFree: lots kB\n").is_empty());
+    }
+
+    #[test]
+    fn publishes_meminfo_in_bytes() {
+        let mut metrics = HashMap::new();
+        insert_meminfo(&parse_meminfo(MEMINFO), &mut metrics);
+
+        assert_eq!(integer(&metrics, "buffers_bytes"), 412040 * 1024);
+        assert_eq!(integer(&metrics, "cached_bytes"), 6790180 * 1024);
+        assert_eq!(integer(&metrics, "hugepage_size_bytes"), 2048 * 1024);
+        // Page counts are not scaled.
+        assert_eq!(integer(&metrics, "hugepages_count"), 8);
+        assert_eq!(integer(&metrics, "hugepages_free"), 6);
+        assert!(!metrics.contains_key("slab_bytes"));
+        assert!(matches!(
+            metrics["committed_percent"],
+            MetricValue::Float(p) if p == 75.0
+        ));
+    }
+
+    #[test]
+    fn skips_committed_percent_without_a_limit() {
+        let mut metrics = HashMap::new();
+        insert_meminfo(
+            &parse_meminfo("Committed_AS: 1024 kB\nCommitLimit: 0 kB\n"),
+            &mut metrics,
+        );
+        assert!(!metrics.contains_key("committed_percent"));
+        assert_eq!(integer(&metrics, "committed_bytes"), 1024 * 1024);
+    }
+
+    #[test]
+    fn matches_field_and_its_zones() {
+        assert!(is_vmstat_field("pgscan_kswapd", "pgscan_kswapd"));
+        assert!(is_vmstat_field("pgscan_kswapd_normal", "pgscan_kswapd"));
+        assert!(is_vmstat_field("pgsteal_direct_dma32", "pgsteal_direct"));
+        assert!(is_vmstat_field("pgscan_direct_movable", "pgscan_direct"));
+    }
+
+    #[test]
+    fn rejects_fields_sharing_a_prefix() {
+        assert!(!is_vmstat_field("pgscan_direct_throttle", "pgscan_direct"));
+        assert!(!is_vmstat_field("pgfault", "pgmajfault"));
+        assert!(!is_vmstat_field("pgmajfault", "pgfault"));
+        assert!(!is_vmstat_field("thp_fault_alloc", "thp_fault_fallback"));
+        assert!(!is_vmstat_field(
+            "thp_fault_fallback_charge",
+            "thp_fault_fallback"
+        ));
+    }
 }
@@ -1,6 +1,14 @@
+pub mod cgroup;
+pub(crate) mod cgroupfs;
+pub mod cgroups;
 pub mod cpu;
+pub(crate) mod delta;
 pub mod disk;
 pub mod memory;
 pub mod network;
+pub mod power;
+pub mod pressure;
 pub mod process;
+pub mod sensors;
+pub mod sockets;
 pub mod system;
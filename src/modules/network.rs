@@ -1,12 +1,123 @@
+use crate::config::{glob_match, NetworkConfig};
 use crate::core::{MetricCollector, MetricData, MetricValue};
+use crate::modules::delta::{rate, DeltaSampler};
 use sysinfo::Networks;
 use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 
-pub struct NetworkCollector;
+// ARPHRD_LOOPBACK from <linux/if_arp.h>, as reported in /sys/class/net/<iface>/type.
+const ARPHRD_LOOPBACK: &str = "772";
+
+// Protocol counters from /proc/net/snmp and /proc/net/netstat as (section, field, key).
+const PROTOCOL_COUNTERS: &[(&str, &str, &str)] = &[
+    ("Tcp", "ActiveOpens", "tcp.active_opens"),
+    ("Tcp", "PassiveOpens", "tcp.passive_opens"),
+    ("Tcp", "AttemptFails", "tcp.connect_failures"),
+    ("Tcp", "EstabResets", "tcp.established_resets"),
+    ("Tcp", "InSegs", "tcp.segments_received"),
+    ("Tcp", "OutSegs", "tcp.segments_sent"),
+    ("Tcp", "RetransSegs", "tcp.segments_retransmitted"),
+    ("Tcp", "InErrs", "tcp.receive_errors"),
+    ("Tcp", "OutRsts", "tcp.resets_sent"),
+    ("TcpExt", "ListenOverflows", "tcp.listen_overflows"),
+    ("TcpExt", "ListenDrops", "tcp.listen_drops"),
+    ("TcpExt", "TCPTimeouts", "tcp.timeouts"),
+    ("TcpExt", "TCPSynRetrans", "tcp.syn_retransmits"),
+    ("TcpExt", "TCPAbortOnTimeout", "tcp.aborts_on_timeout"),
+    ("TcpExt", "TCPBacklogDrop", "tcp.backlog_drops"),
+    ("Udp", "InDatagrams", "udp.datagrams_received"),
+    ("Udp", "OutDatagrams", "udp.datagrams_sent"),
+    ("Udp", "InErrors", "udp.receive_errors"),
+    ("Udp", "NoPorts", "udp.no_port_errors"),
+    ("Udp", "RcvbufErrors", "udp.receive_buffer_errors"),
+    ("Udp", "SndbufErrors", "udp.send_buffer_errors"),
+];
+
+type ProtocolCounters = HashMap<(String, String), u64>;
+
+#[derive(Default)]
+pub struct NetworkCollector {
+    config: NetworkConfig,
+    protocol: DeltaSampler<ProtocolCounters>,
+}
 
 impl NetworkCollector {
     pub fn new() -> Self {
-        NetworkCollector
+        Self::default()
+    }
+
+    pub fn with_config(config: NetworkConfig) -> Self {
+        NetworkCollector {
+            config,
+            protocol: DeltaSampler::default(),
+        }
+    }
+
+    /// Publishes each protocol counter as `<key>_total` plus a `<key>_per_sec` rate.
+    fn collect_protocol(&self, metrics: &mut HashMap<String, MetricValue>) {
+        let Ok((before, after, elapsed)) = self.protocol.sample(read_protocol_counters) else {
+            return;
+        };
+
+        for (section, field, key) in PROTOCOL_COUNTERS {
+            let id = (section.to_string(), field.to_string());
+            let Some(&now) = after.get(&id) else {
+                continue;
+            };
+            let then = before.get(&id).copied().unwrap_or(now);
+            metrics.insert(format!("{}_total", key), MetricValue::Integer(now as i64));
+            metrics.insert(
+                format!("{}_per_sec", key),
+                MetricValue::Float(rate(then, now, elapsed)),
+            );
+        }
+
+        let delta = |field: &str| {
+            let id = ("Tcp".to_string(), field.to_string());
+            match (before.get(&id), after.get(&id)) {
+                (Some(then), Some(now)) => Some(now.saturating_sub(*then)),
+                _ => None,
+            }
+        };
+        if let (Some(retransmitted), Some(sent)) = (delta("RetransSegs"), delta("OutSegs")) {
+            let percent = if sent > 0 {
+                retransmitted as f64 / sent as f64 * 100.0
+            } else {
+                0.0
+            };
+            metrics.insert(
+                "tcp.retransmit_percent".to_string(),
+                MetricValue::Float(percent),
+            );
+        }
+    }
+
+    fn is_included(&self, name: &str) -> bool {
+        let loopback = is_loopback(name);
+        self.passes_filters(name, loopback, !loopback && is_virtual(name))
+    }
+
+    /// Applies the configured filters to an interface already classified from sysfs.
+    fn passes_filters(&self, name: &str, loopback: bool, virtual_iface: bool) -> bool {
+        let config = &self.config;
+        if !config.include_loopback && loopback {
+            return false;
+        }
+        if !config.include_virtual && virtual_iface {
+            return false;
+        }
+        if !config.include_interfaces.is_empty()
+            && !config
+                .include_interfaces
+                .iter()
+                .any(|p| glob_match(p, name))
+        {
+            return false;
+        }
+        !config
+            .exclude_interfaces
+            .iter()
+            .any(|p| glob_match(p, name))
     }
 }
 
@@ -19,23 +130,95 @@ impl MetricCollector for NetworkCollector {
         let mut iface_count: i64 = 0;
         let mut iface_details: Vec<MetricValue> = Vec::new();
 
-        for (name, data) in networks.iter() {
+        let mut metrics = HashMap::new();
+        let mut interfaces: Vec<_> = networks
+            .iter()
+            .filter(|(name, _)| self.is_included(name))
+            .collect();
+        interfaces.sort_by_key(|(name, _)| *name);
+
+        for (name, data) in interfaces {
             let rx = data.total_received();
             let tx = data.total_transmitted();
             total_rx += rx;
             total_tx += tx;
             iface_count += 1;
-            iface_details.push(MetricValue::String(
-                format!("{}: rx={} tx={}", name, format_bytes(rx), format_bytes(tx)),
-            ));
+
+            let operstate = sysfs_value(name, "operstate").unwrap_or_else(|| "unknown".to_string());
+            let ips: Vec<MetricValue> = data
+                .ip_networks()
+                .iter()
+                .map(|ip| MetricValue::String(ip.to_string()))
+                .collect();
+
+            let prefix = format!("interface.{}", name);
+            metrics.insert(
+                format!("{}.rx_bytes_total", prefix),
+                MetricValue::Integer(rx as i64),
+            );
+            metrics.insert(
+                format!("{}.tx_bytes_total", prefix),
+                MetricValue::Integer(tx as i64),
+            );
+            metrics.insert(
+                format!("{}.rx_packets_total", prefix),
+                MetricValue::Integer(data.total_packets_received() as i64),
+            );
+            metrics.insert(
+                format!("{}.tx_packets_total", prefix),
+                MetricValue::Integer(data.total_packets_transmitted() as i64),
+            );
+            metrics.insert(
+                format!("{}.rx_errors_total", prefix),
+                MetricValue::Integer(data.total_errors_on_received() as i64),
+            );
+            metrics.insert(
+                format!("{}.tx_errors_total", prefix),
+                MetricValue::Integer(data.total_errors_on_transmitted() as i64),
+            );
+            // sysinfo doesn't expose drops; they are only available from sysfs on Linux.
+            if let Some(dropped) = sysfs_counter(name, "statistics/rx_dropped") {
+                metrics.insert(
+                    format!("{}.rx_dropped_total", prefix),
+                    MetricValue::Integer(dropped as i64),
+                );
+            }
+            if let Some(dropped) = sysfs_counter(name, "statistics/tx_dropped") {
+                metrics.insert(
+                    format!("{}.tx_dropped_total", prefix),
+                    MetricValue::Integer(dropped as i64),
+                );
+            }
+            metrics.insert(
+                format!("{}.mtu", prefix),
+                MetricValue::Integer(data.mtu() as i64),
+            );
+            metrics.insert(
+                format!("{}.mac", prefix),
+                MetricValue::String(data.mac_address().to_string()),
+            );
+            metrics.insert(format!("{}.ips", prefix), MetricValue::List(ips));
+            metrics.insert(
+                format!("{}.operstate", prefix),
+                MetricValue::String(operstate.clone()),
+            );
+
+            iface_details.push(MetricValue::String(format!(
+                "{} ({}): rx={} tx={}",
+                name,
+                operstate,
+                format_bytes(rx),
+                format_bytes(tx)
+            )));
         }
 
-        let mut metrics = HashMap::new();
         metrics.insert("total_received_bytes".to_string(), MetricValue::Integer(total_rx as i64));
         metrics.insert("total_transmitted_bytes".to_string(), MetricValue::Integer(total_tx as i64));
         metrics.insert("interface_count".to_string(), MetricValue::Integer(iface_count));
         metrics.insert("interfaces".to_string(), MetricValue::List(iface_details));
 
+        self.collect_protocol(&mut metrics);
+
         Ok(MetricData {
             timestamp: std::time::SystemTime::now(),
             metrics,
@@ -45,6 +228,66 @@ impl MetricCollector for NetworkCollector {
     fn name(&self) -> &'static str {
         "network"
     }
+
+    fn prime(&self) -> bool {
+        self.protocol.prime(read_protocol_counters)
+    }
+}
+
+/// Reads both files' `Section: names...` / `Section: values...` line pairs. Only the
+/// snmp file is required; netstat holds the Linux-specific extensions.
+fn read_protocol_counters() -> std::io::Result<ProtocolCounters> {
+    let mut counters = HashMap::new();
+    parse_protocol_table(&std::fs::read_to_string("/proc/net/snmp")?, &mut counters);
+    if let Ok(netstat) = std::fs::read_to_string("/proc/net/netstat") {
+        parse_protocol_table(&netstat, &mut counters);
+    }
+    Ok(counters)
+}
+
+fn parse_protocol_table(contents: &str, counters: &mut ProtocolCounters) {
+    let mut lines = contents.lines();
+    while let (Some(header), Some(values)) = (lines.next(), lines.next()) {
+        let mut header = header.split_whitespace();
+        let mut values = values.split_whitespace();
+        let Some(section) = header.next().and_then(|s| s.strip_suffix(':')) else {
+            continue;
+        };
+        values.next();
+
+        for (field, value) in header.zip(values) {
+            // A few fields are signed (Tcp MaxConn is -1); none of those are counters.
+            if let Ok(value) = value.parse() {
+                counters.insert((section.to_string(), field.to_string()), value);
+            }
+        }
+    }
+}
+
+fn sysfs_path(iface: &str, attr: &str) -> PathBuf {
+    Path::new("/sys/class/net").join(iface).join(attr)
+}
+
+fn sysfs_value(iface: &str, attr: &str) -> Option<String> {
+    std::fs::read_to_string(sysfs_path(iface, attr))
+        .ok()
+        .map(|s| s.trim().to_string())
+}
+
+fn sysfs_counter(iface: &str, attr: &str) -> Option<u64> {
+    sysfs_value(iface, attr)?.parse().ok()
+}
+
+fn is_loopback(iface: &str) -> bool {
+    match sysfs_value(iface, "type") {
+        Some(kind) => kind == ARPHRD_LOOPBACK,
+        None => iface == "lo" || iface.starts_with("lo0"),
+    }
+}
+
+/// Virtual interfaces (bridges, veths, tunnels, ...) have no backing device in sysfs.
+fn is_virtual(iface: &str) -> bool {
+    Path::new("/sys/class/net").join(iface).exists() && !sysfs_path(iface, "device").exists()
 }
 
 fn format_bytes(bytes: u64) -> String {
@@ -58,3 +301,106 @@ fn format_bytes(bytes: u64) -> String {
         format!("{}B", bytes)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SNMP: &str = "\
+Ip: Forwarding DefaultTTL InReceives
+Ip: 1 64 123456
+Tcp: RtoAlgorithm MaxConn ActiveOpens RetransSegs
+Tcp: 1 -1 42 7
+Udp: InDatagrams NoPorts
+Udp: 900 3
+";
+
+    const NETSTAT: &str = "\
+TcpExt: ListenOverflows ListenDrops
+TcpExt: 2 5
+";
+
+    fn get(counters: &ProtocolCounters, section: &str, field: &str) -> Option<u64> {
+        counters
+            .get(&(section.to_string(), field.to_string()))
+            .copied()
+    }
+
+    #[test]
+    fn loopback_is_excluded_unless_asked_for() {
+        let default = NetworkCollector::new();
+        assert!(!default.passes_filters("lo", true, false));
+        assert!(default.passes_filters("eth0", false, false));
+
+        let with_loopback = NetworkCollector::with_config(NetworkConfig {
+            include_loopback: true,
+            ..NetworkConfig::default()
+        });
+        assert!(with_loopback.passes_filters("lo", true, false));
+    }
+
+    #[test]
+    fn virtual_interfaces_can_be_left_out() {
+        let default = NetworkCollector::new();
+        assert!(default.passes_filters("docker0", false, true));
+
+        let physical_only = NetworkCollector::with_config(NetworkConfig {
+            include_virtual: false,
+            ..NetworkConfig::default()
+        });
+        assert!(!physical_only.passes_filters("docker0", false, true));
+        assert!(!physical_only.passes_filters("veth3a1f", false, true));
+        assert!(physical_only.passes_filters("enp3s0", false, false));
+    }
+
+    #[test]
+    fn interface_globs_apply_after_the_kind_filters() {
+        let collector = NetworkCollector::with_config(NetworkConfig {
+            include_interfaces: vec!["en*".to_string(), "wl*".to_string()],
+            exclude_interfaces: vec!["wlan1".to_string()],
+            ..NetworkConfig::default()
+        });
+        assert!(collector.passes_filters("enp3s0", false, false));
+        assert!(collector.passes_filters("wlan0", false, false));
+        assert!(!collector.passes_filters("wlan1", false, false));
+        assert!(!collector.passes_filters("eth0", false, false));
+        assert!(!collector.passes_filters("lo", true, false));
+    }
+
+    #[test]
+    fn loopback_is_recognised_without_sysfs() {
+        assert!(is_loopback("lo"));
+        assert!(is_loopback("lo0"));
+        assert!(!is_loopback("gim-test-nonexistent0"));
+        assert!(!is_virtual("gim-test-nonexistent0"));
+    }
+
+    #[test]
+    fn pairs_header_and_value_lines() {
+        let mut counters = HashMap::new();
+        parse_protocol_table(SNMP, &mut counters);
+        assert_eq!(get(&counters, "Ip", "InReceives"), Some(123456));
+        assert_eq!(get(&counters, "Tcp", "ActiveOpens"), Some(42));
+        assert_eq!(get(&counters, "Tcp", "RetransSegs"), Some(7));
+        assert_eq!(get(&counters, "Udp", "NoPorts"), Some(3));
+        // Signed fields are not counters and are left out.
+        assert_eq!(get(&counters, "Tcp", "MaxConn"), None);
+    }
+
+    #[test]
+    fn merges_netstat_extensions() {
+        let mut counters = HashMap::new();
+        parse_protocol_table(SNMP, &mut counters);
+        parse_protocol_table(NETSTAT, &mut counters);
+        assert_eq!(get(&counters, "TcpExt", "ListenDrops"), Some(5));
+        assert_eq!(get(&counters, "Tcp", "ActiveOpens"), Some(42));
+    }
+
+    #[test]
+    fn ignores_lines_without_a_section() {
+        let mut counters = HashMap::new();
+        parse_protocol_table("garbage here\n1 2\nUdp: NoPorts\nUdp: 4\n", &mut counters);
+        assert_eq!(counters.len(), 1);
+        assert_eq!(get(&counters, "Udp", "NoPorts"), Some(4));
+    }
+}
@@ -0,0 +1,299 @@
+use crate::core::{MetricCollector, MetricData, MetricValue};
+use std::collections::HashMap;
+use std::path::Path;
+
+const POWER_SUPPLY_DIR: &str = "/sys/class/power_supply";
+
+#[derive(Default)]
+pub struct PowerCollector;
+
+impl PowerCollector {
+    pub fn new() -> Self {
+        PowerCollector
+    }
+}
+
+impl MetricCollector for PowerCollector {
+    fn collect(&self) -> Result<MetricData, Box<dyn std::error::Error>> {
+        let mut metrics = HashMap::new();
+        let mut batteries: Vec<MetricValue> = Vec::new();
+        let mut ac_online: Option<bool> = None;
+
+        // Servers and VMs usually have no power supplies at all; that's not an error.
+        let mut supplies: Vec<_> = std::fs::read_dir(POWER_SUPPLY_DIR)
+            .map(|entries| entries.flatten().map(|e| e.path()).collect())
+            .unwrap_or_default();
+        supplies.sort();
+
+        for dir in supplies {
+            let name = dir
+                .file_name()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .to_string();
+            match read_attr(&dir, "type").as_deref() {
+                Some("Mains") | Some("USB") => {
+                    if let Some(online) = read_number(&dir, "online") {
+                        ac_online = Some(ac_online.unwrap_or(false) || online > 0.0);
+                    }
+                }
+                Some("Battery") => {
+                    // Peripheral batteries (mice, headsets) report scope=Device.
+                    if read_attr(&dir, "scope").as_deref() == Some("Device") {
+                        continue;
+                    }
+                    let summary = collect_battery(&dir, &name, &mut metrics);
+                    batteries.push(MetricValue::String(summary));
+                }
+                _ => {}
+            }
+        }
+
+        if let Some(online) = ac_online {
+            metrics.insert("ac_online".to_string(), MetricValue::Boolean(online));
+        }
+        metrics.insert(
+            "battery_count".to_string(),
+            MetricValue::Integer(batteries.len() as i64),
+        );
+        metrics.insert("batteries".to_string(), MetricValue::List(batteries));
+
+        Ok(MetricData {
+            timestamp: std::time::SystemTime::now(),
+            metrics,
+        })
+    }
+
+    fn name(&self) -> &'static str {
+        "power"
+    }
+}
+
+/// Readings of one battery, converted to watt-hours and watts.
+struct BatteryReading {
+    status: String,
+    capacity_percent: Option<f64>,
+    energy_now_wh: Option<f64>,
+    energy_full_wh: Option<f64>,
+    power_watts: Option<f64>,
+}
+
+impl BatteryReading {
+    fn read(dir: &Path) -> Self {
+        Self::from_attrs(|attr| read_attr(dir, attr))
+    }
+
+    /// Builds a reading from the supply's sysfs attributes, looked up by name.
+    fn from_attrs(attr: impl Fn(&str) -> Option<String>) -> Self {
+        let number = |name: &str| attr(name)?.parse::<f64>().ok().filter(|v| v.is_finite());
+
+        // Batteries report either energy (µWh, µW) or charge (µAh, µA) counters; charge is
+        // converted to energy using the present voltage (µV).
+        let voltage = number("voltage_now").map(|uv| uv / 1e6);
+        let energy_wh = |energy: &str, charge: &str| {
+            number(energy)
+                .map(|uwh| uwh / 1e6)
+                .or_else(|| Some(number(charge)? / 1e6 * voltage?))
+        };
+
+        BatteryReading {
+            status: attr("status").unwrap_or_else(|| "Unknown".to_string()),
+            capacity_percent: number("capacity"),
+            energy_now_wh: energy_wh("energy_now", "charge_now"),
+            energy_full_wh: energy_wh("energy_full", "charge_full"),
+            power_watts: number("power_now")
+                .map(|uw| uw / 1e6)
+                .or_else(|| Some(number("current_now")? / 1e6 * voltage?))
+                .map(f64::abs),
+        }
+    }
+}
+
+/// Publishes `battery.<name>.*` and returns a one-line summary for the `batteries` list.
+fn collect_battery(dir: &Path, name: &str, metrics: &mut HashMap<String, MetricValue>) -> String {
+    insert_battery(name, &BatteryReading::read(dir), metrics)
+}
+
+fn insert_battery(
+    name: &str,
+    battery: &BatteryReading,
+    metrics: &mut HashMap<String, MetricValue>,
+) -> String {
+    let prefix = format!("battery.{}", name);
+    let status = &battery.status;
+    let energy_now = battery.energy_now_wh;
+    let energy_full = battery.energy_full_wh;
+    let power_watts = battery.power_watts;
+
+    let charge_percent = battery
+        .capacity_percent
+        .or_else(|| match (energy_now, energy_full) {
+            (Some(now), Some(full)) if full > 0.0 => Some(now / full * 100.0),
+            _ => None,
+        });
+
+    metrics.insert(
+        format!("{}.status", prefix),
+        MetricValue::String(status.to_lowercase()),
+    );
+    if let Some(percent) = charge_percent {
+        metrics.insert(
+            format!("{}.charge_percent", prefix),
+            MetricValue::Float(percent),
+        );
+    }
+    if let Some(watts) = power_watts {
+        metrics.insert(format!("{}.power_watts", prefix), MetricValue::Float(watts));
+    }
+    if let Some(now) = energy_now {
+        metrics.insert(format!("{}.energy_wh", prefix), MetricValue::Float(now));
+    }
+    if let Some(full) = energy_full {
+        metrics.insert(
+            format!("{}.energy_full_wh", prefix),
+            MetricValue::Float(full),
+        );
+    }
+
+    // Estimates only make sense while energy is actually flowing.
+    let hours = match (status.as_str(), energy_now, energy_full, power_watts) {
+        ("Discharging", Some(now), _, Some(watts)) if watts > 0.0 => {
+            Some(("time_to_empty_seconds", now / watts))
+        }
+        ("Charging", Some(now), Some(full), Some(watts)) if watts > 0.0 => {
+            Some(("time_to_full_seconds", (full - now).max(0.0) / watts))
+        }
+        _ => None,
+    };
+    if let Some((key, hours)) = hours {
+        metrics.insert(
+            format!("{}.{}", prefix, key),
+            MetricValue::Integer((hours * 3600.0) as i64),
+        );
+    }
+
+    let mut summary = format!("{}: {}", name, status.to_lowercase());
+    if let Some(percent) = charge_percent {
+        summary.push_str(&format!(" {:.0}%", percent));
+    }
+    if let Some((key, hours)) = hours {
+        let minutes = (hours * 60.0) as u64;
+        let label = if key == "time_to_empty_seconds" {
+            "left"
+        } else {
+            "to full"
+        };
+        summary.push_str(&format!(
+            ", {}h{:02}m {}",
+            minutes / 60,
+            minutes % 60,
+            label
+        ));
+    }
+    summary
+}
+
+fn read_attr(dir: &Path, attr: &str) -> Option<String> {
+    std::fs::read_to_string(dir.join(attr))
+        .ok()
+        .map(|s| s.trim().to_string())
+}
+
+fn read_number(dir: &Path, attr: &str) -> Option<f64> {
+    read_attr(dir, attr)?.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reading(attrs: &[(&str, &str)]) -> BatteryReading {
+        let attrs: HashMap<&str, &str> = attrs.iter().copied().collect();
+        BatteryReading::from_attrs(|name| attrs.get(name).map(|v| v.to_string()))
+    }
+
+    fn float(metrics: &HashMap<String, MetricValue>, key: &str) -> f64 {
+        match metrics.get(key) {
+            Some(MetricValue::Float(v)) => *v,
+            other => panic!("{key}: {other:?}"),
+        }
+    }
+
+    fn integer(metrics: &HashMap<String, MetricValue>, key: &str) -> i64 {
+        match metrics.get(key) {
+            Some(MetricValue::Integer(v)) => *v,
+            other => panic!("{key}: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn converts_charge_to_energy_at_the_present_voltage() {
+        // 2.5 Ah of 5 Ah left at 12 V, drawing 1.25 A.
+        let battery = reading(&[
+            ("status", "Discharging"),
+            ("voltage_now", "12000000"),
+            ("charge_now", "2500000"),
+            ("charge_full", "5000000"),
+            ("current_now", "-1250000"),
+        ]);
+        assert_eq!(battery.energy_now_wh, Some(30.0));
+        assert_eq!(battery.energy_full_wh, Some(60.0));
+        assert_eq!(battery.power_watts, Some(15.0));
+
+        let mut metrics = HashMap::new();
+        let summary = insert_battery("BAT0", &battery, &mut metrics);
+        assert_eq!(float(&metrics, "battery.BAT0.charge_percent"), 50.0);
+        assert_eq!(
+            integer(&metrics, "battery.BAT0.time_to_empty_seconds"),
+            7200
+        );
+        assert_eq!(summary, "BAT0: discharging 50%, 2h00m left");
+    }
+
+    #[test]
+    fn estimates_time_to_full_while_charging() {
+        let battery = reading(&[
+            ("status", "Charging"),
+            ("capacity", "75"),
+            ("energy_now", "45000000"),
+            ("energy_full", "60000000"),
+            ("power_now", "30000000"),
+        ]);
+        let mut metrics = HashMap::new();
+        let summary = insert_battery("BAT1", &battery, &mut metrics);
+        assert_eq!(integer(&metrics, "battery.BAT1.time_to_full_seconds"), 1800);
+        assert!(!metrics.contains_key("battery.BAT1.time_to_empty_seconds"));
+        assert_eq!(summary, "BAT1: charging 75%, 0h30m to full");
+    }
+
+    #[test]
+    fn no_estimate_without_current() {
+        for status in ["Discharging", "Charging"] {
+            let battery = reading(&[
+                ("status", status),
+                ("voltage_now", "12000000"),
+                ("charge_now", "2500000"),
+                ("charge_full", "5000000"),
+                ("current_now", "0"),
+            ]);
+            let mut metrics = HashMap::new();
+            insert_battery("BAT0", &battery, &mut metrics);
+            assert_eq!(float(&metrics, "battery.BAT0.power_watts"), 0.0);
+            assert!(!metrics.contains_key("battery.BAT0.time_to_empty_seconds"));
+            assert!(!metrics.contains_key("battery.BAT0.time_to_full_seconds"));
+        }
+    }
+
+    #[test]
+    fn charge_without_voltage_reports_no_energy() {
+        let battery = reading(&[
+            ("status", "Full"),
+            ("capacity", "100"),
+            ("charge_now", "5000000"),
+            ("current_now", "nan"),
+        ]);
+        assert_eq!(battery.energy_now_wh, None);
+        assert_eq!(battery.power_watts, None);
+        assert_eq!(battery.capacity_percent, Some(100.0));
+    }
+}
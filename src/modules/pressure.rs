@@ -0,0 +1,61 @@
+use crate::core::{MetricCollector, MetricData, MetricValue};
+use crate::modules::cgroupfs::{current_cgroup, insert_pressure, is_host_root, read_pressure};
+use std::collections::HashMap;
+use std::path::Path;
+
+const RESOURCES: &[&str] = &["cpu", "memory", "io"];
+
+#[derive(Default)]
+pub struct PressureCollector;
+
+impl PressureCollector {
+    pub fn new() -> Self {
+        PressureCollector
+    }
+}
+
+impl MetricCollector for PressureCollector {
+    fn collect(&self) -> Result<MetricData, Box<dyn std::error::Error>> {
+        if !Path::new("/proc/pressure").exists() {
+            return Err("PSI is not available (needs Linux 4.20+ with CONFIG_PSI)".into());
+        }
+
+        let mut metrics = HashMap::new();
+        let mut worst: f64 = 0.0;
+
+        for resource in RESOURCES {
+            let Some(lines) = read_pressure(&Path::new("/proc/pressure").join(resource)) else {
+                continue;
+            };
+            for (kind, line) in &lines {
+                if kind == "some" {
+                    worst = worst.max(line.avg10);
+                }
+            }
+            insert_pressure(&mut metrics, resource, &lines);
+        }
+
+        // The cgroup gim runs in, e.g. a container or a systemd service. At the host
+        // root these would just repeat the system-wide figures.
+        if let Some(cgroup) = current_cgroup().filter(|dir| !is_host_root(dir)) {
+            for resource in RESOURCES {
+                let file = cgroup.join(format!("{}.pressure", resource));
+                if let Some(lines) = read_pressure(&file) {
+                    insert_pressure(&mut metrics, &format!("cgroup.{}", resource), &lines);
+                }
+            }
+        }
+
+        // Highest share of time any task was stalled on some resource over the last 10s.
+        metrics.insert("max_some_avg10".to_string(), MetricValue::Float(worst));
+
+        Ok(MetricData {
+            timestamp: std::time::SystemTime::now(),
+            metrics,
+        })
+    }
+
+    fn name(&self) -> &'static str {
+        "pressure"
+    }
+}
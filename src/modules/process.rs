@@ -2,6 +2,7 @@ use crate::core::{MetricCollector, MetricData, MetricValue};
 use sysinfo::{System, RefreshKind, ProcessRefreshKind};
 use std::collections::HashMap;
 
+#[derive(Default)]
 pub struct ProcessCollector;
 
 impl ProcessCollector {
@@ -20,7 +21,7 @@ impl MetricCollector for ProcessCollector {
         let total = processes.len() as i64;
 
         let mut by_memory: Vec<_> = processes.values().collect();
-        by_memory.sort_by(|a, b| b.memory().cmp(&a.memory()));
+        by_memory.sort_by_key(|p| std::cmp::Reverse(p.memory()));
 
         let top_mem: Vec<MetricValue> = by_memory
             .iter()
@@ -0,0 +1,166 @@
+use crate::core::{MetricCollector, MetricData, MetricValue};
+use std::collections::{HashMap, HashSet};
+use sysinfo::Components;
+
+#[derive(Default)]
+pub struct SensorsCollector;
+
+impl SensorsCollector {
+    pub fn new() -> Self {
+        SensorsCollector
+    }
+}
+
+impl MetricCollector for SensorsCollector {
+    fn collect(&self) -> Result<MetricData, Box<dyn std::error::Error>> {
+        let components = Components::new_with_refreshed_list();
+
+        let mut metrics = HashMap::new();
+        let mut sensors: Vec<MetricValue> = Vec::new();
+        let mut names = SensorNames::default();
+        let mut hottest: Option<f64> = None;
+        let mut closest_to_critical: Option<f64> = None;
+
+        for component in components.list() {
+            let Some(temperature) = component.temperature().map(f64::from) else {
+                continue;
+            };
+
+            let name = names.assign(component.label());
+            let prefix = format!("sensor.{}", name);
+            metrics.insert(
+                format!("{}.label", prefix),
+                MetricValue::String(component.label().to_string()),
+            );
+            metrics.insert(
+                format!("{}.temperature_celsius", prefix),
+                MetricValue::Float(temperature),
+            );
+            if let Some(max) = component.max() {
+                metrics.insert(
+                    format!("{}.max_celsius", prefix),
+                    MetricValue::Float(f64::from(max)),
+                );
+            }
+            if let Some(critical) = component.critical().map(f64::from).filter(|c| *c > 0.0) {
+                let percent = temperature / critical * 100.0;
+                metrics.insert(
+                    format!("{}.critical_celsius", prefix),
+                    MetricValue::Float(critical),
+                );
+                metrics.insert(
+                    format!("{}.critical_percent", prefix),
+                    MetricValue::Float(percent),
+                );
+                closest_to_critical = Some(closest_to_critical.map_or(percent, |p| p.max(percent)));
+            }
+
+            hottest = Some(hottest.map_or(temperature, |t| t.max(temperature)));
+            sensors.push(MetricValue::String(format!(
+                "{}: {:.1}°C",
+                component.label(),
+                temperature
+            )));
+        }
+
+        metrics.insert(
+            "sensor_count".to_string(),
+            MetricValue::Integer(sensors.len() as i64),
+        );
+        metrics.insert("sensors".to_string(), MetricValue::List(sensors));
+        if let Some(hottest) = hottest {
+            metrics.insert(
+                "max_temperature_celsius".to_string(),
+                MetricValue::Float(hottest),
+            );
+        }
+        if let Some(percent) = closest_to_critical {
+            metrics.insert(
+                "max_critical_percent".to_string(),
+                MetricValue::Float(percent),
+            );
+        }
+
+        Ok(MetricData {
+            timestamp: std::time::SystemTime::now(),
+            metrics,
+        })
+    }
+
+    fn name(&self) -> &'static str {
+        "sensors"
+    }
+}
+
+/// Hands out a distinct key per sensor.
+///
+/// Labels repeat across chips (e.g. several "Composite" NVMe sensors), so later ones get
+/// `_2`, `_3`, ... appended, skipping any key a sensor is already using.
+#[derive(Default)]
+struct SensorNames {
+    used: HashSet<String>,
+}
+
+impl SensorNames {
+    fn assign(&mut self, label: &str) -> String {
+        let base = sensor_key(label);
+        let mut name = base.clone();
+        let mut n = 1;
+        while !self.used.insert(name.clone()) {
+            n += 1;
+            name = format!("{}_{}", base, n);
+        }
+        name
+    }
+}
+
+/// Turns a label like "coretemp Package id 0" into `coretemp_package_id_0` so it can be
+/// used in metric paths, alert rules and expressions.
+fn sensor_key(label: &str) -> String {
+    let mut key = String::new();
+    for c in label.chars() {
+        if c.is_ascii_alphanumeric() {
+            key.push(c.to_ascii_lowercase());
+        } else if !key.is_empty() && !key.ends_with('_') {
+            key.push('_');
+        }
+    }
+    let key = key.trim_end_matches('_');
+    if key.is_empty() {
+        "unknown".to_string()
+    } else {
+        key.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalizes_labels_to_keys() {
+        assert_eq!(sensor_key("coretemp Package id 0"), "coretemp_package_id_0");
+        assert_eq!(sensor_key("nvme Composite"), "nvme_composite");
+        assert_eq!(sensor_key("  acpitz--temp1  "), "acpitz_temp1");
+        assert_eq!(sensor_key("Tctl (°C)"), "tctl_c");
+        assert_eq!(sensor_key("°"), "unknown");
+        assert_eq!(sensor_key(""), "unknown");
+    }
+
+    #[test]
+    fn suffixes_repeated_labels() {
+        let mut names = SensorNames::default();
+        assert_eq!(names.assign("nvme Composite"), "nvme_composite");
+        assert_eq!(names.assign("nvme Composite"), "nvme_composite_2");
+        assert_eq!(names.assign("nvme-Composite"), "nvme_composite_3");
+        assert_eq!(names.assign("Core 0"), "core_0");
+    }
+
+    #[test]
+    fn suffixes_never_collide_with_real_labels() {
+        let mut names = SensorNames::default();
+        assert_eq!(names.assign("temp"), "temp");
+        assert_eq!(names.assign("temp 2"), "temp_2");
+        assert_eq!(names.assign("temp"), "temp_3");
+    }
+}
@@ -0,0 +1,281 @@
+use crate::core::{MetricCollector, MetricData, MetricValue};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::net::{Ipv4Addr, Ipv6Addr};
+
+// Kernel TCP states as numbered in include/net/tcp_states.h.
+const TCP_STATES: &[(u8, &str)] = &[
+    (0x01, "established"),
+    (0x02, "syn_sent"),
+    (0x03, "syn_recv"),
+    (0x04, "fin_wait1"),
+    (0x05, "fin_wait2"),
+    (0x06, "time_wait"),
+    (0x07, "close"),
+    (0x08, "close_wait"),
+    (0x09, "last_ack"),
+    (0x0A, "listen"),
+    (0x0B, "closing"),
+    (0x0C, "new_syn_recv"),
+];
+
+const TCP_LISTEN: u8 = 0x0A;
+// An unconnected UDP socket shows up as TCP_CLOSE, i.e. bound and waiting for datagrams.
+const UDP_UNCONNECTED: u8 = 0x07;
+
+#[derive(Default)]
+pub struct SocketsCollector;
+
+impl SocketsCollector {
+    pub fn new() -> Self {
+        SocketsCollector
+    }
+}
+
+#[derive(Clone)]
+struct SocketEntry {
+    local: String,
+    state: u8,
+}
+
+impl MetricCollector for SocketsCollector {
+    fn collect(&self) -> Result<MetricData, Box<dyn std::error::Error>> {
+        // IPv4 tables are always present on Linux; the IPv6 ones vanish when IPv6 is disabled.
+        let tcp = [
+            read_table("/proc/net/tcp")?,
+            read_table("/proc/net/tcp6").unwrap_or_default(),
+        ]
+        .concat();
+        let udp = [
+            read_table("/proc/net/udp")?,
+            read_table("/proc/net/udp6").unwrap_or_default(),
+        ]
+        .concat();
+
+        let mut metrics = HashMap::new();
+        let mut listening = BTreeSet::new();
+
+        let mut states: BTreeMap<&str, i64> =
+            TCP_STATES.iter().map(|(_, name)| (*name, 0)).collect();
+        for entry in &tcp {
+            if let Some((_, name)) = TCP_STATES.iter().find(|(code, _)| *code == entry.state) {
+                *states.entry(name).or_default() += 1;
+            }
+            if entry.state == TCP_LISTEN {
+                listening.insert(format!("tcp {}", entry.local));
+            }
+        }
+        for entry in udp.iter().filter(|e| e.state == UDP_UNCONNECTED) {
+            listening.insert(format!("udp {}", entry.local));
+        }
+
+        for (name, count) in states {
+            metrics.insert(format!("tcp_state.{}", name), MetricValue::Integer(count));
+        }
+        metrics.insert(
+            "tcp_sockets".to_string(),
+            MetricValue::Integer(tcp.len() as i64),
+        );
+        metrics.insert(
+            "udp_sockets".to_string(),
+            MetricValue::Integer(udp.len() as i64),
+        );
+        metrics.insert(
+            "listening_sockets".to_string(),
+            MetricValue::Integer(listening.len() as i64),
+        );
+        metrics.insert(
+            "listening".to_string(),
+            MetricValue::List(listening.into_iter().map(MetricValue::String).collect()),
+        );
+
+        if let Ok(sockstat) = read_sockstat("/proc/net/sockstat") {
+            let get = |proto: &str, field: &str| {
+                sockstat
+                    .get(&(proto.to_string(), field.to_string()))
+                    .copied()
+            };
+            if let Some(used) = get("sockets", "used") {
+                metrics.insert("sockets_used".to_string(), MetricValue::Integer(used));
+            }
+            if let Some(orphan) = get("TCP", "orphan") {
+                metrics.insert("tcp_orphan".to_string(), MetricValue::Integer(orphan));
+            }
+            if let Some(alloc) = get("TCP", "alloc") {
+                metrics.insert("tcp_alloc".to_string(), MetricValue::Integer(alloc));
+            }
+            // Socket memory is accounted in pages.
+            if let Some(pages) = get("TCP", "mem") {
+                metrics.insert(
+                    "tcp_memory_bytes".to_string(),
+                    MetricValue::Integer(pages * page_size() as i64),
+                );
+            }
+            if let Some(pages) = get("UDP", "mem") {
+                metrics.insert(
+                    "udp_memory_bytes".to_string(),
+                    MetricValue::Integer(pages * page_size() as i64),
+                );
+            }
+        }
+
+        Ok(MetricData {
+            timestamp: std::time::SystemTime::now(),
+            metrics,
+        })
+    }
+
+    fn name(&self) -> &'static str {
+        "sockets"
+    }
+}
+
+fn read_table(path: &str) -> std::io::Result<Vec<SocketEntry>> {
+    Ok(parse_table(&std::fs::read_to_string(path)?))
+}
+
+/// Parses a `/proc/net/{tcp,udp}[6]` table, skipping its header line.
+fn parse_table(contents: &str) -> Vec<SocketEntry> {
+    contents
+        .lines()
+        .skip(1)
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let local = decode_address(fields.get(1)?)?;
+            let state = u8::from_str_radix(fields.get(3)?, 16).ok()?;
+            Some(SocketEntry { local, state })
+        })
+        .collect()
+}
+
+/// Decodes `0100007F:1F90` (IPv4) or its 32-digit IPv6 form into `addr:port`. Addresses
+/// are written as native-endian 32-bit words, the port as big-endian hex.
+fn decode_address(field: &str) -> Option<String> {
+    let (addr, port) = field.split_once(':')?;
+    let port = u16::from_str_radix(port, 16).ok()?;
+    if !addr.len().is_multiple_of(8) {
+        return None;
+    }
+
+    let words: Vec<u32> = (0..addr.len() / 8)
+        .map(|i| u32::from_str_radix(&addr[i * 8..i * 8 + 8], 16))
+        .collect::<Result<_, _>>()
+        .ok()?;
+
+    match words.as_slice() {
+        [v4] => Some(format!("{}:{}", Ipv4Addr::from(v4.to_ne_bytes()), port)),
+        [a, b, c, d] => {
+            let mut bytes = [0u8; 16];
+            for (chunk, word) in bytes.chunks_mut(4).zip([a, b, c, d]) {
+                chunk.copy_from_slice(&word.to_ne_bytes());
+            }
+            Some(format!("[{}]:{}", Ipv6Addr::from(bytes), port))
+        }
+        _ => None,
+    }
+}
+
+fn read_sockstat(path: &str) -> std::io::Result<HashMap<(String, String), i64>> {
+    Ok(parse_sockstat(&std::fs::read_to_string(path)?))
+}
+
+/// Parses lines like `TCP: inuse 4 orphan 0 tw 0 alloc 4 mem 1` into (protocol, field) pairs.
+fn parse_sockstat(contents: &str) -> HashMap<(String, String), i64> {
+    let mut values = HashMap::new();
+
+    for line in contents.lines() {
+        let Some((proto, rest)) = line.split_once(':') else {
+            continue;
+        };
+        let fields: Vec<&str> = rest.split_whitespace().collect();
+        for pair in fields.chunks(2) {
+            if let [field, value] = pair {
+                if let Ok(value) = value.parse() {
+                    values.insert((proto.to_string(), field.to_string()), value);
+                }
+            }
+        }
+    }
+
+    values
+}
+
+#[cfg(unix)]
+fn page_size() -> u64 {
+    // SAFETY: sysconf has no preconditions.
+    let size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) };
+    u64::try_from(size).ok().filter(|&s| s > 0).unwrap_or(4096)
+}
+
+#[cfg(not(unix))]
+fn page_size() -> u64 {
+    4096
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The kernel prints each 32-bit address word in host byte order.
+    fn word(bytes: [u8; 4]) -> String {
+        format!("{:08X}", u32::from_ne_bytes(bytes))
+    }
+
+    #[test]
+    fn decodes_ipv4_addresses() {
+        let field = format!("{}:1F90", word([127, 0, 0, 1]));
+        assert_eq!(decode_address(&field).as_deref(), Some("127.0.0.1:8080"));
+        let any = format!("{}:0035", word([0, 0, 0, 0]));
+        assert_eq!(decode_address(&any).as_deref(), Some("0.0.0.0:53"));
+    }
+
+    #[test]
+    fn decodes_ipv6_addresses() {
+        let zero = word([0, 0, 0, 0]);
+        let loopback = format!("{0}{0}{0}{1}:0016", zero, word([0, 0, 0, 1]));
+        assert_eq!(decode_address(&loopback).as_deref(), Some("[::1]:22"));
+    }
+
+    #[test]
+    fn rejects_malformed_addresses() {
+        assert_eq!(decode_address("0100007F"), None);
+        assert_eq!(decode_address("0100007F:XYZ"), None);
+        assert_eq!(decode_address("0100007G:0050"), None);
+        assert_eq!(decode_address("0100007F00:0050"), None);
+    }
+
+    #[test]
+    fn parses_socket_tables() {
+        let table = format!(
+            "  sl  local_address rem_address   st tx_queue rx_queue\n   \
+             0: {0}:0016 00000000:0000 0A 00000000:00000000\n   \
+             1: {0}:0016 {0}:C350 01 00000000:00000000\n   \
+             2: garbage\n",
+            word([10, 0, 0, 1])
+        );
+        let entries = parse_table(&table);
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].local, "10.0.0.1:22");
+        assert_eq!(entries[0].state, TCP_LISTEN);
+        assert_eq!(entries[1].state, 0x01);
+    }
+
+    #[test]
+    fn parses_sockstat() {
+        let sockstat = parse_sockstat(
+            "sockets: used 312\n\
+             TCP: inuse 12 orphan 0 tw 3 alloc 15 mem 4\n\
+             UDP: inuse 2 mem 1\n\
+             FRAG: inuse 0 memory 0\n",
+        );
+        let get = |proto: &str, field: &str| {
+            sockstat
+                .get(&(proto.to_string(), field.to_string()))
+                .copied()
+        };
+        assert_eq!(get("sockets", "used"), Some(312));
+        assert_eq!(get("TCP", "tw"), Some(3));
+        assert_eq!(get("TCP", "mem"), Some(4));
+        assert_eq!(get("UDP", "mem"), Some(1));
+        assert_eq!(get("UDP", "tw"), None);
+    }
+}
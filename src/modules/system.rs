@@ -2,6 +2,7 @@ use crate::core::{MetricCollector, MetricData, MetricValue};
 use sysinfo::System;
 use std::collections::HashMap;
 
+#[derive(Default)]
 pub struct SystemCollector;
 
 impl SystemCollector {
@@ -23,6 +24,10 @@ impl MetricCollector for SystemCollector {
         if let Some(kernel) = System::kernel_version() {
             metrics.insert("kernel_version".to_string(), MetricValue::String(kernel));
         }
+        metrics.insert(
+            "os_type".to_string(),
+            MetricValue::String(std::env::consts::OS.to_string()),
+        );
         if let Some(host) = System::host_name() {
             metrics.insert("hostname".to_string(), MetricValue::String(host));
         }
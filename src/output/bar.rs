@@ -0,0 +1,180 @@
+use ratatui::style::Color;
+use serde_json::{json, Value};
+
+use crate::config::{parse_color, Config};
+use crate::core::{MetricPath, MetricValue, Severity};
+use crate::engine::MetricsSnapshot;
+use crate::error::{GimError, Result};
+
+use super::metric_value_to_display;
+
+struct BarItem<'a> {
+    path: &'a MetricPath,
+    label: String,
+    text: String,
+    severity: Severity,
+    percentage: Option<f64>,
+}
+
+pub fn parse_metrics(selectors: &[String]) -> Result<Vec<MetricPath>> {
+    selectors
+        .iter()
+        .map(|s| MetricPath::parse(s).ok_or_else(|| GimError::InvalidMetric(s.clone())))
+        .collect()
+}
+
+/// Protocol header plus the opening of the infinite array.
+pub fn i3bar_header() -> String {
+    format!("{}\n[\n", json!({ "version": 1 }))
+}
+
+/// One status line for i3bar/swaybar. Every line after the first is prefixed with a comma
+/// to continue the infinite array.
+pub fn format_i3bar(
+    snapshot: &MetricsSnapshot,
+    metrics: &[MetricPath],
+    config: &Config,
+    first: bool,
+) -> String {
+    let blocks: Vec<Value> = bar_items(snapshot, metrics, config)
+        .iter()
+        .map(|item| {
+            json!({
+                "name": item.path.module,
+                "instance": item.path.key,
+                "full_text": format!("{} {}", item.label, item.text),
+                "color": color_hex(item_color(item, config)),
+                "urgent": item.severity == Severity::Critical,
+            })
+        })
+        .collect();
+
+    let line = Value::Array(blocks).to_string();
+    if first {
+        format!("{}\n", line)
+    } else {
+        format!(",{}\n", line)
+    }
+}
+
+/// One JSON object for a waybar custom module with `"return-type": "json"`.
+pub fn format_waybar(
+    snapshot: &MetricsSnapshot,
+    metrics: &[MetricPath],
+    config: &Config,
+) -> String {
+    let items = bar_items(snapshot, metrics, config);
+
+    let text = items
+        .iter()
+        .map(|item| format!("{} {}", item.label, item.text))
+        .collect::<Vec<_>>()
+        .join("  ");
+    let tooltip = items
+        .iter()
+        .map(|item| format!("{}: {}", item.path, item.text))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let class = match items.iter().map(|item| item.severity).max() {
+        Some(Severity::Critical) => "critical",
+        Some(Severity::Warning) => "warning",
+        _ => "ok",
+    };
+    let percentage = items
+        .iter()
+        .filter_map(|item| item.percentage)
+        .fold(None, |max: Option<f64>, p| {
+            Some(max.map_or(p, |m| m.max(p)))
+        })
+        .map(|p| p.clamp(0.0, 100.0).round() as u64);
+
+    let mut output = json!({
+        "text": text,
+        "tooltip": tooltip,
+        "class": class,
+    });
+    if let Some(percentage) = percentage {
+        output["percentage"] = json!(percentage);
+    }
+    format!("{}\n", output)
+}
+
+fn bar_items<'a>(
+    snapshot: &MetricsSnapshot,
+    metrics: &'a [MetricPath],
+    config: &Config,
+) -> Vec<BarItem<'a>> {
+    metrics
+        .iter()
+        .map(|path| {
+            let value = snapshot.get(path);
+            let number = value.and_then(MetricValue::as_f64);
+            let is_percent = path.key.ends_with("_percent");
+
+            let text = match (value, number) {
+                (Some(_), Some(n)) if is_percent => format!("{:.0}%", n),
+                (Some(v), _) => metric_value_to_display(&path.key, v),
+                (None, _) => "n/a".to_string(),
+            };
+            let severity = number
+                .zip(config.print.threshold_for(&path.module, &path.key))
+                .map(|(n, threshold)| threshold.severity(n))
+                .unwrap_or(Severity::Ok);
+
+            BarItem {
+                path,
+                label: item_label(path, metrics, config),
+                text,
+                severity,
+                percentage: number.filter(|_| is_percent),
+            }
+        })
+        .collect()
+}
+
+/// The module label is enough when a module contributes a single metric; otherwise
+/// the metric key tells them apart.
+fn item_label(path: &MetricPath, metrics: &[MetricPath], config: &Config) -> String {
+    let shared = metrics.iter().filter(|m| m.module == path.module).count() > 1;
+    let label = &config.theme.module(&path.module).label;
+    if shared {
+        path.key.clone()
+    } else if label.is_empty() {
+        path.module.to_uppercase()
+    } else {
+        label.clone()
+    }
+}
+
+fn item_color(item: &BarItem, config: &Config) -> Color {
+    match item.severity {
+        Severity::Critical => parse_color(&config.theme.chrome.error),
+        Severity::Warning => parse_color(&config.theme.chrome.warning),
+        Severity::Ok => parse_color(&config.theme.module(&item.path.module).fg),
+    }
+}
+
+/// i3bar and waybar only understand `#rrggbb`, so named colors are mapped to the
+/// usual xterm palette.
+fn color_hex(color: Color) -> String {
+    let (r, g, b) = match color {
+        Color::Rgb(r, g, b) => (r, g, b),
+        Color::Black => (0x00, 0x00, 0x00),
+        Color::Red => (0xcd, 0x00, 0x00),
+        Color::Green => (0x00, 0xcd, 0x00),
+        Color::Yellow => (0xcd, 0xcd, 0x00),
+        Color::Blue => (0x00, 0x00, 0xee),
+        Color::Magenta => (0xcd, 0x00, 0xcd),
+        Color::Cyan => (0x00, 0xcd, 0xcd),
+        Color::Gray => (0xe5, 0xe5, 0xe5),
+        Color::DarkGray => (0x7f, 0x7f, 0x7f),
+        Color::LightRed => (0xff, 0x00, 0x00),
+        Color::LightGreen => (0x00, 0xff, 0x00),
+        Color::LightYellow => (0xff, 0xff, 0x00),
+        Color::LightBlue => (0x5c, 0x5c, 0xff),
+        Color::LightMagenta => (0xff, 0x00, 0xff),
+        Color::LightCyan => (0x00, 0xff, 0xff),
+        _ => (0xff, 0xff, 0xff),
+    };
+    format!("#{:02x}{:02x}{:02x}", r, g, b)
+}
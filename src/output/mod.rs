@@ -1,5 +1,14 @@
+pub mod bar;
+
+use std::io::IsTerminal;
+
+use crossterm::style::{Color, Stylize};
+
+use crate::alerts::{Alert, AlertState};
+use crate::anomaly::Anomaly;
 use crate::cli::OutputFormatArg;
-use crate::core::{MetricData, MetricValue};
+use crate::config::{parse_color, ColorMode, Config, PrintConfig, ThemeConfig, Threshold};
+use crate::core::{MetricData, MetricValue, Severity};
 use crate::engine::MetricsSnapshot;
 
 #[derive(Clone)]
@@ -29,25 +38,161 @@ impl OutputFormat {
     }
 }
 
-pub fn format_snapshot(snapshot: &MetricsSnapshot, format: &OutputFormat) -> String {
+/// Colors and thresholds used by the table format. Other formats ignore it.
+pub struct OutputStyle {
+    color: bool,
+    theme: ThemeConfig,
+    print: PrintConfig,
+}
+
+impl OutputStyle {
+    pub fn from_config(config: &Config) -> Self {
+        let color = match config.print.color {
+            ColorMode::Always => true,
+            ColorMode::Never => false,
+            ColorMode::Auto => {
+                std::env::var_os("NO_COLOR").is_none_or(|v| v.is_empty())
+                    && std::io::stdout().is_terminal()
+            }
+        };
+
+        Self {
+            color,
+            theme: config.theme.clone(),
+            print: config.print.clone(),
+        }
+    }
+
+    pub fn plain() -> Self {
+        Self {
+            color: false,
+            theme: ThemeConfig::default(),
+            print: PrintConfig::default(),
+        }
+    }
+
+    fn paint(&self, text: &str, color: &str) -> String {
+        if self.color {
+            format!("{}", text.with(Color::from(parse_color(color))))
+        } else {
+            text.to_string()
+        }
+    }
+
+    fn threshold(&self, module: &str, key: &str) -> Option<&Threshold> {
+        self.print.threshold_for(module, key)
+    }
+}
+
+pub fn format_snapshot(
+    snapshot: &MetricsSnapshot,
+    format: &OutputFormat,
+    style: &OutputStyle,
+) -> String {
     let mut output = String::new();
     for (name, data) in &snapshot.modules {
-        output.push_str(&format!("=== {} ===\n", name.to_uppercase()));
-        output.push_str(&format_output(data, format));
+        let header = format!("=== {} ===", name.to_uppercase());
+        match format {
+            OutputFormat::Table => {
+                output.push_str(&style.paint(&header, &style.theme.module(name).fg));
+                output.push('\n');
+                output.push_str(&format_table(data, name, style));
+            }
+            OutputFormat::Json => {
+                let anomalies: Vec<&Anomaly> = snapshot
+                    .anomalies
+                    .iter()
+                    .filter(|a| a.path.module == *name)
+                    .collect();
+                output.push_str(&header);
+                output.push('\n');
+                output.push_str(&format_json(data, &anomalies));
+            }
+            OutputFormat::Raw => {
+                output.push_str(&header);
+                output.push('\n');
+                output.push_str(&format_raw(data));
+            }
+        }
+        output.push('\n');
+    }
+
+    if !snapshot.alerts.is_empty() {
+        let header = "=== ALERTS ===";
+        match format {
+            OutputFormat::Table => output.push_str(&style.paint(header, &style.theme.chrome.error)),
+            OutputFormat::Json | OutputFormat::Raw => output.push_str(header),
+        }
+        output.push('\n');
+        output.push_str(&format_alerts(&snapshot.alerts, format, style));
         output.push('\n');
     }
     output
 }
 
+fn format_alerts(alerts: &[Alert], format: &OutputFormat, style: &OutputStyle) -> String {
+    match format {
+        OutputFormat::Json => {
+            let items: Vec<serde_json::Value> = alerts
+                .iter()
+                .map(|alert| {
+                    serde_json::json!({
+                        "rule": alert.rule,
+                        "metric": alert.path.to_string(),
+                        "severity": alert.severity_label(),
+                        "state": alert.state.label(),
+                        "value": alert.value,
+                        "since": format!("{:?}", alert.since),
+                    })
+                })
+                .collect();
+            serde_json::to_string_pretty(&items).unwrap_or_else(|_| "[]".to_string()) + "\n"
+        }
+        OutputFormat::Raw => alerts
+            .iter()
+            .map(|alert| {
+                format!(
+                    "{}={} {} {:.2}\n",
+                    alert.path,
+                    alert.severity_label(),
+                    alert.state.label(),
+                    alert.value
+                )
+            })
+            .collect(),
+        OutputFormat::Table => alerts
+            .iter()
+            .map(|alert| {
+                let status = format!(
+                    "{:<8} {:<8}",
+                    alert.severity_label().to_uppercase(),
+                    alert.state.label()
+                );
+                let color = match (alert.state, alert.severity) {
+                    (AlertState::Resolved, _) | (_, Severity::Ok) => &style.theme.chrome.header,
+                    (_, Severity::Warning) => &style.theme.chrome.warning,
+                    (_, Severity::Critical) => &style.theme.chrome.error,
+                };
+                format!(
+                    " {} {:.2}  {}\n",
+                    style.paint(&status, color),
+                    alert.value,
+                    alert.rule
+                )
+            })
+            .collect(),
+    }
+}
+
 pub fn format_output(data: &MetricData, format: &OutputFormat) -> String {
     match format {
-        OutputFormat::Json => format_json(data),
-        OutputFormat::Table => format_table(data),
+        OutputFormat::Json => format_json(data, &[]),
+        OutputFormat::Table => format_table(data, "", &OutputStyle::plain()),
         OutputFormat::Raw => format_raw(data),
     }
 }
 
-fn format_json(data: &MetricData) -> String {
+fn format_json(data: &MetricData, anomalies: &[&Anomaly]) -> String {
     let mut output = std::collections::HashMap::new();
     output.insert(
         "timestamp".to_string(),
@@ -60,6 +205,23 @@ fn format_json(data: &MetricData) -> String {
     }
     output.insert("metrics".to_string(), serde_json::Value::Object(metrics));
 
+    if !anomalies.is_empty() {
+        let flags: serde_json::Map<String, serde_json::Value> = anomalies
+            .iter()
+            .map(|a| {
+                (
+                    a.path.key.clone(),
+                    serde_json::json!({
+                        "baseline": a.baseline,
+                        "score": a.score,
+                        "direction": a.direction(),
+                    }),
+                )
+            })
+            .collect();
+        output.insert("anomalies".to_string(), serde_json::Value::Object(flags));
+    }
+
     serde_json::to_string_pretty(&output).unwrap_or_else(|_| "{}".to_string())
 }
 
@@ -77,50 +239,75 @@ fn metric_value_to_json(value: &MetricValue) -> serde_json::Value {
     }
 }
 
-fn format_table(data: &MetricData) -> String {
+fn format_table(data: &MetricData, module: &str, style: &OutputStyle) -> String {
     let mut output = String::new();
+    let theme = style.theme.module(module);
+    let border = &style.theme.chrome.border;
 
     let mut entries: Vec<_> = data.metrics.iter().collect();
-    entries.sort_by_key(|(k, _)| k.clone());
+    entries.sort_by_key(|(k, _)| *k);
 
     let max_key_len = entries.iter().map(|(k, _)| k.len()).max().unwrap_or(20);
     let width = max_key_len.max(20);
 
-    output.push_str(&format!("{:─<w$}┬{:─<40}\n", "", "", w = width + 2));
+    let rule = |corner: &str| {
+        style.paint(
+            &format!("{:─<w$}{}{:─<40}", "", corner, "", w = width + 2),
+            border,
+        )
+    };
+
+    output.push_str(&rule("┬"));
+    output.push('\n');
     for (key, value) in &entries {
+        let display = metric_value_to_display(key, value);
+        let severity = value
+            .as_f64()
+            .zip(style.threshold(module, key))
+            .map(|(v, threshold)| threshold.severity(v))
+            .unwrap_or(Severity::Ok);
+        let display = match severity {
+            Severity::Critical => style.paint(&display, &style.theme.chrome.error),
+            Severity::Warning => style.paint(&display, &style.theme.chrome.warning),
+            Severity::Ok => display,
+        };
+
         output.push_str(&format!(
-            " {:<w$} │ {}\n",
-            key,
-            metric_value_to_display(value),
-            w = width,
+            " {} {} {}\n",
+            style.paint(&format!("{:<w$}", key, w = width), &theme.fg),
+            style.paint("│", border),
+            display,
         ));
     }
-    output.push_str(&format!("{:─<w$}┴{:─<40}\n", "", "", w = width + 2));
+    output.push_str(&rule("┴"));
+    output.push('\n');
 
     output
 }
 
 fn format_raw(data: &MetricData) -> String {
     let mut entries: Vec<_> = data.metrics.iter().collect();
-    entries.sort_by_key(|(k, _)| k.clone());
+    entries.sort_by_key(|(k, _)| *k);
 
     entries
         .iter()
-        .map(|(key, value)| format!("{}={}", key, metric_value_to_display(value)))
+        .map(|(key, value)| format!("{}={}", key, metric_value_to_display(key, value)))
         .collect::<Vec<_>>()
         .join("\n")
         + "\n"
 }
 
-fn metric_value_to_display(value: &MetricValue) -> String {
+/// Integers are humanized as byte sizes only for `*bytes*` keys; counts stay as-is.
+fn metric_value_to_display(key: &str, value: &MetricValue) -> String {
     match value {
-        MetricValue::Integer(i) => format_bytes_or_int(*i),
+        MetricValue::Integer(i) if key.contains("bytes") => format_bytes_or_int(*i),
+        MetricValue::Integer(i) => i.to_string(),
         MetricValue::Float(f) => format!("{:.2}", f),
         MetricValue::String(s) => s.clone(),
         MetricValue::Boolean(b) => b.to_string(),
         MetricValue::List(items) => items
             .iter()
-            .map(metric_value_to_display)
+            .map(|item| metric_value_to_display(key, item))
             .collect::<Vec<_>>()
             .join(", "),
     }
@@ -138,3 +325,59 @@ fn format_bytes_or_int(value: i64) -> String {
         value.to_string()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::MetricPath;
+    use std::time::SystemTime;
+
+    fn snapshot_with_alert() -> MetricsSnapshot {
+        MetricsSnapshot {
+            modules: vec![(
+                "cpu".to_string(),
+                MetricData {
+                    timestamp: SystemTime::now(),
+                    metrics: [("cpu_usage_percent".to_string(), MetricValue::Float(97.0))]
+                        .into_iter()
+                        .collect(),
+                },
+            )],
+            alerts: vec![Alert {
+                rule: "cpu.cpu_usage_percent > 90 => critical".to_string(),
+                path: MetricPath::parse("cpu.cpu_usage_percent").unwrap(),
+                severity: Severity::Critical,
+                state: AlertState::Firing,
+                value: 97.0,
+                since: SystemTime::now(),
+                changed: true,
+            }],
+            anomalies: Vec::new(),
+        }
+    }
+
+    fn colored() -> OutputStyle {
+        OutputStyle {
+            color: true,
+            ..OutputStyle::plain()
+        }
+    }
+
+    #[test]
+    fn only_table_output_is_colored() {
+        let snapshot = snapshot_with_alert();
+        for format in [OutputFormat::Json, OutputFormat::Raw] {
+            let output = format_snapshot(&snapshot, &format, &colored());
+            assert!(!output.contains('\x1b'), "{}", output);
+            assert!(output.contains("=== ALERTS ===\n"));
+        }
+        let table = format_snapshot(&snapshot, &OutputFormat::Table, &colored());
+        assert!(table.contains('\x1b'));
+    }
+
+    #[test]
+    fn raw_alert_lines() {
+        let output = format_snapshot(&snapshot_with_alert(), &OutputFormat::Raw, &colored());
+        assert!(output.ends_with("=== ALERTS ===\ncpu.cpu_usage_percent=critical firing 97.00\n\n"));
+    }
+}
@@ -0,0 +1,237 @@
+use std::collections::VecDeque;
+use std::io::{BufWriter, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::time::{Duration, UNIX_EPOCH};
+
+use crate::config::GraphiteConfig;
+use crate::engine::MetricsSnapshot;
+use crate::error::{GimError, Result};
+use crate::sinks::{format_number, hostname, numeric_metrics, sanitize, Backoff, MetricSink};
+
+/// Wait before the first reconnect attempt after a failure; doubles while the server stays down.
+const RECONNECT_BACKOFF: Duration = Duration::from_secs(1);
+
+pub struct GraphiteSink {
+    config: GraphiteConfig,
+    stream: Option<BufWriter<TcpStream>>,
+    pending: VecDeque<String>,
+    dropped: u64,
+    reconnect: Backoff,
+}
+
+impl GraphiteSink {
+    pub fn new(config: GraphiteConfig) -> Self {
+        Self {
+            config,
+            stream: None,
+            pending: VecDeque::new(),
+            dropped: 0,
+            reconnect: Backoff::new(RECONNECT_BACKOFF),
+        }
+    }
+
+    fn enqueue(&mut self, snapshot: &MetricsSnapshot) {
+        // Dots separate path components in Graphite, so a FQDN must not add levels.
+        let host = sanitize(&hostname(snapshot)).replace('.', "_");
+
+        for (module, data) in &snapshot.modules {
+            let timestamp = data
+                .timestamp
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0);
+
+            for (key, value) in numeric_metrics(data) {
+                let mut path = Vec::new();
+                if !self.config.prefix.is_empty() {
+                    path.push(sanitize(&self.config.prefix));
+                }
+                path.push(host.clone());
+                path.push(sanitize(module));
+                path.push(sanitize(key));
+
+                if self.pending.len() >= self.config.max_buffered_points {
+                    self.pending.pop_front();
+                    self.dropped += 1;
+                }
+                self.pending.push_back(format!(
+                    "{} {} {}\n",
+                    path.join("."),
+                    format_number(value),
+                    timestamp
+                ));
+            }
+        }
+    }
+
+    fn connect(&self) -> std::io::Result<TcpStream> {
+        let timeout = Duration::from_millis(self.config.timeout_ms);
+        let addr = (self.config.host.as_str(), self.config.port)
+            .to_socket_addrs()?
+            .next()
+            .ok_or_else(|| {
+                std::io::Error::new(
+                    std::io::ErrorKind::NotFound,
+                    format!("could not resolve {}", self.config.host),
+                )
+            })?;
+        let stream = TcpStream::connect_timeout(&addr, timeout)?;
+        stream.set_write_timeout(Some(timeout))?;
+        Ok(stream)
+    }
+
+    /// Writes out everything buffered. Lines stay buffered until the write succeeds, so a
+    /// partly sent batch is sent again in full; Graphite keeps the last value per
+    /// timestamp, so the repeats are harmless.
+    fn flush(&mut self) -> std::io::Result<()> {
+        if self.stream.is_none() {
+            self.stream = Some(BufWriter::new(self.connect()?));
+        }
+
+        let Some(stream) = self.stream.as_mut() else {
+            return Ok(());
+        };
+        let written = self
+            .pending
+            .iter()
+            .try_for_each(|line| stream.write_all(line.as_bytes()))
+            .and_then(|()| stream.flush());
+        if written.is_ok() {
+            self.pending.clear();
+        }
+        written
+    }
+
+    /// Closes the connection without trying to send what is still in its buffer.
+    fn disconnect(&mut self) {
+        if let Some(stream) = self.stream.take() {
+            drop(stream.into_parts());
+        }
+    }
+}
+
+impl MetricSink for GraphiteSink {
+    fn send(&mut self, snapshot: &MetricsSnapshot) -> Result<()> {
+        self.enqueue(snapshot);
+
+        // While the server is down, points are only buffered until the next reconnect attempt.
+        if self.stream.is_none() && !self.reconnect.ready() {
+            return Ok(());
+        }
+
+        match self.flush() {
+            Ok(()) => {
+                self.reconnect.succeeded();
+                Ok(())
+            }
+            Err(e) => {
+                self.disconnect();
+                self.reconnect.failed();
+                let mut msg = format!("{} ({} points buffered", e, self.pending.len());
+                if self.dropped > 0 {
+                    msg.push_str(&format!(", {} dropped", self.dropped));
+                }
+                msg.push(')');
+                Err(GimError::Sink {
+                    sink: "graphite".to_string(),
+                    source: msg.into(),
+                })
+            }
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        "graphite"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{MetricData, MetricValue};
+    use std::io::Read;
+    use std::net::TcpListener;
+    use std::time::Instant;
+
+    fn snapshot() -> MetricsSnapshot {
+        let module = |name: &str, key: &str, value: MetricValue| {
+            (
+                name.to_string(),
+                MetricData {
+                    timestamp: UNIX_EPOCH + Duration::from_secs(1_700_000_000),
+                    metrics: [(key.to_string(), value)].into_iter().collect(),
+                },
+            )
+        };
+        MetricsSnapshot {
+            modules: vec![
+                module(
+                    "system",
+                    "hostname",
+                    MetricValue::String("web.example.com".to_string()),
+                ),
+                module("cpu", "usage_percent", MetricValue::Float(12.5)),
+            ],
+            alerts: Vec::new(),
+            anomalies: Vec::new(),
+        }
+    }
+
+    fn sink(port: u16, max_buffered_points: usize) -> GraphiteSink {
+        GraphiteSink::new(GraphiteConfig {
+            port,
+            max_buffered_points,
+            ..GraphiteConfig::default()
+        })
+    }
+
+    /// A port nothing listens on.
+    fn closed_port() -> u16 {
+        TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port()
+    }
+
+    #[test]
+    fn writes_plaintext_lines() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let mut sink = sink(listener.local_addr().unwrap().port(), 100);
+
+        sink.send(&snapshot()).unwrap();
+        sink.disconnect();
+
+        let mut received = String::new();
+        let (mut conn, _) = listener.accept().unwrap();
+        conn.read_to_string(&mut received).unwrap();
+        assert_eq!(
+            received,
+            "gim.web_example_com.cpu.usage_percent 12.5 1700000000\n"
+        );
+    }
+
+    #[test]
+    fn buffers_while_down_and_waits_before_reconnecting() {
+        let mut sink = sink(closed_port(), 100);
+
+        assert!(sink.send(&snapshot()).is_err());
+        assert_eq!(sink.pending.len(), 1);
+
+        // Inside the reconnect backoff nothing is attempted, so this returns at once.
+        let started = Instant::now();
+        sink.send(&snapshot()).unwrap();
+        assert!(started.elapsed() < Duration::from_millis(100));
+        assert_eq!(sink.pending.len(), 2);
+    }
+
+    #[test]
+    fn drops_oldest_points_beyond_the_buffer() {
+        let mut sink = sink(closed_port(), 2);
+        for _ in 0..3 {
+            sink.enqueue(&snapshot());
+        }
+        assert_eq!(sink.pending.len(), 2);
+        assert_eq!(sink.dropped, 1);
+    }
+}
@@ -0,0 +1,216 @@
+use std::io::{Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::time::Duration;
+
+/// Minimal HTTP/1.1 POST over plain TCP, enough to talk to a local collector or webhook
+/// receiver without pulling in a full client. Only `http://` URLs are supported.
+pub(crate) fn post(
+    url: &str,
+    content_type: &str,
+    headers: &[(String, String)],
+    body: &[u8],
+    timeout: Duration,
+) -> std::io::Result<u16> {
+    let target = HttpUrl::parse(url)?;
+
+    let addr = (target.host.as_str(), target.port)
+        .to_socket_addrs()?
+        .next()
+        .ok_or_else(|| invalid_input(format!("could not resolve {}", target.host)))?;
+    let mut stream = TcpStream::connect_timeout(&addr, timeout)?;
+    stream.set_read_timeout(Some(timeout))?;
+    stream.set_write_timeout(Some(timeout))?;
+
+    let mut request = format!(
+        "POST {} HTTP/1.1\r\nHost: {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n",
+        target.path,
+        target.authority,
+        content_type,
+        body.len()
+    );
+    for (name, value) in headers {
+        request.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    request.push_str("\r\n");
+
+    stream.write_all(request.as_bytes())?;
+    stream.write_all(body)?;
+    stream.flush()?;
+
+    let mut response = Vec::new();
+    let mut buf = [0u8; 1024];
+    // Only the status line matters; stop reading once it has arrived.
+    while !response.contains(&b'\n') {
+        let n = stream.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        response.extend_from_slice(&buf[..n]);
+    }
+
+    let status_line = String::from_utf8_lossy(&response);
+    status_line
+        .split_whitespace()
+        .nth(1)
+        .and_then(|code| code.parse().ok())
+        .ok_or_else(|| {
+            std::io::Error::new(std::io::ErrorKind::InvalidData, "malformed HTTP response")
+        })
+}
+
+struct HttpUrl {
+    host: String,
+    authority: String,
+    port: u16,
+    path: String,
+}
+
+impl HttpUrl {
+    fn parse(url: &str) -> std::io::Result<Self> {
+        let rest = url
+            .strip_prefix("http://")
+            .ok_or_else(|| invalid_input(format!("only http:// URLs are supported: {}", url)))?;
+
+        let (authority, path) = match rest.find('/') {
+            Some(idx) => (&rest[..idx], &rest[idx..]),
+            None => (rest, "/"),
+        };
+
+        let (host, port) = if let Some(bracketed) = authority.strip_prefix('[') {
+            let (host, after) = bracketed
+                .split_once(']')
+                .ok_or_else(|| invalid_input(format!("invalid IPv6 host in {}", url)))?;
+            (host, after.strip_prefix(':'))
+        } else {
+            match authority.split_once(':') {
+                Some((host, port)) => (host, Some(port)),
+                None => (authority, None),
+            }
+        };
+
+        let port = match port {
+            Some(port) => port
+                .parse()
+                .map_err(|_| invalid_input(format!("invalid port in {}", url)))?,
+            None => 80,
+        };
+
+        if host.is_empty() {
+            return Err(invalid_input(format!("missing host in {}", url)));
+        }
+
+        Ok(Self {
+            host: host.to_string(),
+            authority: authority.to_string(),
+            port,
+            path: path.to_string(),
+        })
+    }
+}
+
+fn invalid_input(msg: String) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::InvalidInput, msg)
+}
+
+/// A local HTTP receiver for sink tests.
+#[cfg(test)]
+pub(crate) mod test_server {
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+    use std::sync::mpsc::{self, Receiver};
+
+    pub(crate) struct Request {
+        pub head: String,
+        pub body: String,
+    }
+
+    /// Answers one request per status in `statuses`, in order, and passes each request on.
+    /// Returns the port it listens on.
+    pub(crate) fn serve(statuses: Vec<u16>) -> (u16, Receiver<Request>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let (tx, rx) = mpsc::channel();
+
+        std::thread::spawn(move || {
+            for status in statuses {
+                let Ok((stream, _)) = listener.accept() else {
+                    return;
+                };
+                let mut reader = BufReader::new(stream);
+                let mut head = String::new();
+                let mut content_length = 0;
+                loop {
+                    let mut line = String::new();
+                    if reader.read_line(&mut line).unwrap() == 0 || line == "\r\n" {
+                        break;
+                    }
+                    if let Some(len) = line.to_ascii_lowercase().strip_prefix("content-length:") {
+                        content_length = len.trim().parse().unwrap();
+                    }
+                    head.push_str(&line);
+                }
+                let mut body = vec![0u8; content_length];
+                reader.read_exact(&mut body).unwrap();
+
+                let response = format!("HTTP/1.1 {} Test\r\nContent-Length: 0\r\n\r\n", status);
+                reader.get_mut().write_all(response.as_bytes()).unwrap();
+                let request = Request {
+                    head,
+                    body: String::from_utf8(body).unwrap(),
+                };
+                if tx.send(request).is_err() {
+                    return;
+                }
+            }
+        });
+
+        (port, rx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_http_urls() {
+        let url = HttpUrl::parse("http://collector:4318/v1/metrics").unwrap();
+        assert_eq!(
+            (url.host.as_str(), url.port, url.path.as_str()),
+            ("collector", 4318, "/v1/metrics")
+        );
+
+        let url = HttpUrl::parse("http://[::1]:9000").unwrap();
+        assert_eq!(
+            (url.host.as_str(), url.port, url.path.as_str()),
+            ("::1", 9000, "/")
+        );
+        assert_eq!(url.authority, "[::1]:9000");
+
+        assert_eq!(HttpUrl::parse("http://example.com/x").unwrap().port, 80);
+        assert!(HttpUrl::parse("https://example.com/").is_err());
+        assert!(HttpUrl::parse("http://:80/").is_err());
+        assert!(HttpUrl::parse("http://host:port/").is_err());
+    }
+
+    #[test]
+    fn posts_body_and_returns_status() {
+        let (port, requests) = test_server::serve(vec![202]);
+        let headers = vec![("X-Token".to_string(), "secret".to_string())];
+        let status = post(
+            &format!("http://127.0.0.1:{}/ingest", port),
+            "application/json",
+            &headers,
+            b"{\"ok\":true}",
+            Duration::from_secs(5),
+        )
+        .unwrap();
+        assert_eq!(status, 202);
+
+        let request = requests.recv().unwrap();
+        assert!(request.head.starts_with("POST /ingest HTTP/1.1\r\n"));
+        assert!(request.head.contains("Content-Type: application/json\r\n"));
+        assert!(request.head.contains("X-Token: secret\r\n"));
+        assert_eq!(request.body, "{\"ok\":true}");
+    }
+}
@@ -0,0 +1,195 @@
+pub mod graphite;
+mod http;
+pub mod notify;
+pub mod otlp;
+pub mod statsd;
+
+use std::time::{Duration, Instant};
+
+use crate::config::SinksConfig;
+use crate::core::MetricData;
+use crate::engine::MetricsSnapshot;
+use crate::error::Result;
+
+use self::graphite::GraphiteSink;
+use self::notify::{ExecHookSink, WebhookSink};
+use self::otlp::OtlpSink;
+use self::statsd::StatsdSink;
+
+pub trait MetricSink {
+    fn send(&mut self, snapshot: &MetricsSnapshot) -> Result<()>;
+    fn name(&self) -> &'static str;
+}
+
+pub struct Sinks {
+    sinks: Vec<Box<dyn MetricSink>>,
+}
+
+impl Sinks {
+    pub fn from_config(config: &SinksConfig) -> Result<Self> {
+        let mut sinks: Vec<Box<dyn MetricSink>> = Vec::new();
+
+        if let Some(statsd) = &config.statsd {
+            sinks.push(Box::new(StatsdSink::new(statsd.clone())?));
+        }
+        if let Some(otlp) = &config.otlp {
+            sinks.push(Box::new(OtlpSink::new(otlp.clone())));
+        }
+        if let Some(graphite) = &config.graphite {
+            sinks.push(Box::new(GraphiteSink::new(graphite.clone())));
+        }
+        if let Some(webhook) = &config.webhook {
+            sinks.push(Box::new(WebhookSink::new(webhook.clone())));
+        }
+        // Without a command there is nothing to run, so the hook is left out entirely.
+        if let Some(exec) = config
+            .exec
+            .as_ref()
+            .filter(|e| !e.command.trim().is_empty())
+        {
+            sinks.push(Box::new(ExecHookSink::new(exec.clone())));
+        }
+
+        Ok(Self { sinks })
+    }
+
+    pub fn dispatch(&mut self, snapshot: &MetricsSnapshot) {
+        for sink in &mut self.sinks {
+            if let Err(e) = sink.send(snapshot) {
+                eprintln!("warning: {}", e);
+            }
+        }
+    }
+}
+
+/// Longest a sink waits between attempts to reach a destination that keeps failing.
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+/// Spaces out attempts to reach a destination that keeps failing, so a sink that is down
+/// doesn't stall every sample. The wait starts at `base` and doubles with each
+/// consecutive failure, up to a minute.
+pub(crate) struct Backoff {
+    base: Duration,
+    failures: u32,
+    next_attempt: Option<Instant>,
+}
+
+impl Backoff {
+    pub(crate) fn new(base: Duration) -> Self {
+        Self {
+            base,
+            failures: 0,
+            next_attempt: None,
+        }
+    }
+
+    /// Whether the wait after the last failure has passed.
+    pub(crate) fn ready(&self) -> bool {
+        self.next_attempt.is_none_or(|at| Instant::now() >= at)
+    }
+
+    pub(crate) fn failed(&mut self) {
+        let wait = self
+            .base
+            .saturating_mul(1 << self.failures.min(16))
+            .min(MAX_BACKOFF);
+        self.failures += 1;
+        self.next_attempt = Some(Instant::now() + wait);
+    }
+
+    pub(crate) fn succeeded(&mut self) {
+        self.failures = 0;
+        self.next_attempt = None;
+    }
+}
+
+/// Numeric metrics of a module, sorted by key. Strings and lists are skipped.
+pub(crate) fn numeric_metrics(data: &MetricData) -> Vec<(&str, f64)> {
+    let mut values: Vec<(&str, f64)> = data
+        .metrics
+        .iter()
+        .filter_map(|(key, value)| value.as_f64().map(|v| (key.as_str(), v)))
+        .filter(|(_, v)| v.is_finite())
+        .collect();
+    values.sort_by(|a, b| a.0.cmp(b.0));
+    values
+}
+
+/// Hostname from the `system` module when it was collected, otherwise asked directly.
+pub(crate) fn hostname(snapshot: &MetricsSnapshot) -> String {
+    snapshot
+        .modules
+        .iter()
+        .find(|(name, _)| name == "system")
+        .and_then(|(_, data)| match data.metrics.get("hostname") {
+            Some(crate::core::MetricValue::String(host)) => Some(host.clone()),
+            _ => None,
+        })
+        .or_else(sysinfo::System::host_name)
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+/// Replaces characters that carry meaning in line protocols with `_`.
+pub(crate) fn sanitize(name: &str) -> String {
+    name.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '_' || c == '-' || c == '.' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+pub(crate) fn format_number(value: f64) -> String {
+    if value.fract() == 0.0 && value.abs() < 1e15 {
+        format!("{:.0}", value)
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backoff_doubles_up_to_the_cap() {
+        let mut backoff = Backoff::new(Duration::from_secs(20));
+        assert!(backoff.ready());
+
+        let wait_after_failure = |backoff: &mut Backoff| {
+            backoff.failed();
+            backoff.next_attempt.unwrap() - Instant::now()
+        };
+        let first = wait_after_failure(&mut backoff);
+        assert!(first > Duration::from_secs(19) && first <= Duration::from_secs(20));
+        assert!(!backoff.ready());
+        let second = wait_after_failure(&mut backoff);
+        assert!(second > Duration::from_secs(39) && second <= Duration::from_secs(40));
+        let third = wait_after_failure(&mut backoff);
+        assert!(third > Duration::from_secs(59) && third <= MAX_BACKOFF);
+
+        backoff.succeeded();
+        assert!(backoff.ready());
+        let reset = wait_after_failure(&mut backoff);
+        assert!(reset <= Duration::from_secs(20));
+    }
+
+    #[test]
+    fn exec_hook_without_a_command_is_skipped() {
+        let config = SinksConfig {
+            exec: Some(crate::config::ExecHookConfig::default()),
+            ..SinksConfig::default()
+        };
+        assert!(Sinks::from_config(&config).unwrap().sinks.is_empty());
+    }
+
+    #[test]
+    fn zero_backoff_retries_on_the_next_sample() {
+        let mut backoff = Backoff::new(Duration::ZERO);
+        backoff.failed();
+        assert!(backoff.ready());
+    }
+}
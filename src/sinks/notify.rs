@@ -0,0 +1,364 @@
+use std::collections::HashMap;
+use std::process::{Child, Command, Stdio};
+use std::time::{Duration, Instant, UNIX_EPOCH};
+
+use serde_json::json;
+
+use crate::alerts::{Alert, AlertState};
+use crate::config::{ExecHookConfig, WebhookConfig};
+use crate::engine::MetricsSnapshot;
+use crate::error::{GimError, Result};
+use crate::sinks::{hostname, http, Backoff, MetricSink};
+
+/// Wait before retrying a receiver or hook that failed; doubles while it keeps failing.
+const NOTIFY_BACKOFF: Duration = Duration::from_secs(1);
+
+/// De-duplicates and rate-limits alert notifications per rule.
+///
+/// Only a change between firing and resolved is worth telling anyone about, and at most
+/// once per cooldown. A rule that flaps inside the cooldown is held back; once the
+/// cooldown passes, whatever state it settled in is sent if it differs from the last one
+/// delivered.
+struct NotifyGate {
+    cooldown: Duration,
+    rules: HashMap<String, RuleGate>,
+}
+
+struct RuleGate {
+    latest: Alert,
+    sent_state: AlertState,
+    sent_at: Option<Instant>,
+}
+
+impl NotifyGate {
+    fn new(cooldown_secs: u64) -> Self {
+        Self {
+            cooldown: Duration::from_secs(cooldown_secs),
+            rules: HashMap::new(),
+        }
+    }
+
+    fn due(&mut self, alerts: &[Alert]) -> Vec<Alert> {
+        for alert in alerts {
+            if alert.state == AlertState::Pending {
+                continue;
+            }
+            self.rules
+                .entry(alert.rule.clone())
+                .and_modify(|gate| gate.latest = alert.clone())
+                .or_insert_with(|| RuleGate {
+                    latest: alert.clone(),
+                    sent_state: AlertState::Resolved,
+                    sent_at: None,
+                });
+        }
+
+        let cooldown = self.cooldown;
+        self.rules
+            .values()
+            .filter(|gate| gate.latest.state != gate.sent_state)
+            .filter(|gate| gate.sent_at.is_none_or(|at| at.elapsed() >= cooldown))
+            .map(|gate| gate.latest.clone())
+            .collect()
+    }
+
+    fn mark_sent(&mut self, alert: &Alert) {
+        if let Some(gate) = self.rules.get_mut(&alert.rule) {
+            gate.sent_state = alert.state;
+            gate.sent_at = Some(Instant::now());
+        }
+    }
+}
+
+pub struct WebhookSink {
+    config: WebhookConfig,
+    headers: Vec<(String, String)>,
+    gate: NotifyGate,
+    backoff: Backoff,
+}
+
+impl WebhookSink {
+    pub fn new(config: WebhookConfig) -> Self {
+        let headers = config
+            .headers
+            .iter()
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect();
+        Self {
+            gate: NotifyGate::new(config.cooldown_secs),
+            headers,
+            config,
+            backoff: Backoff::new(NOTIFY_BACKOFF),
+        }
+    }
+
+    fn deliver(&self, alert: &Alert, host: &str) -> std::result::Result<(), String> {
+        let payload = json!({
+            "source": "gim",
+            "host": host,
+            "rule": alert.rule,
+            "metric": alert.path.to_string(),
+            "severity": alert.severity_label(),
+            "state": alert.state.label(),
+            "value": alert.value,
+            "since": unix_secs(alert),
+        });
+
+        let status = http::post(
+            &self.config.url,
+            "application/json",
+            &self.headers,
+            payload.to_string().as_bytes(),
+            Duration::from_millis(self.config.timeout_ms),
+        )
+        .map_err(|e| e.to_string())?;
+        if !(200..300).contains(&status) {
+            return Err(format!("receiver responded with HTTP {}", status));
+        }
+        Ok(())
+    }
+}
+
+impl MetricSink for WebhookSink {
+    /// Undelivered notifications stay due in the gate. While the receiver is failing,
+    /// they are retried after a backoff rather than on every sample.
+    fn send(&mut self, snapshot: &MetricsSnapshot) -> Result<()> {
+        let due = self.gate.due(&snapshot.alerts);
+        if due.is_empty() || !self.backoff.ready() {
+            return Ok(());
+        }
+
+        let host = hostname(snapshot);
+        for alert in due {
+            if let Err(e) = self.deliver(&alert, &host) {
+                self.backoff.failed();
+                return Err(sink_error("webhook", e));
+            }
+            self.gate.mark_sent(&alert);
+        }
+        self.backoff.succeeded();
+        Ok(())
+    }
+
+    fn name(&self) -> &'static str {
+        "webhook"
+    }
+}
+
+pub struct ExecHookSink {
+    config: ExecHookConfig,
+    gate: NotifyGate,
+    running: Vec<Child>,
+    backoff: Backoff,
+}
+
+impl ExecHookSink {
+    pub fn new(config: ExecHookConfig) -> Self {
+        Self {
+            gate: NotifyGate::new(config.cooldown_secs),
+            config,
+            running: Vec::new(),
+            backoff: Backoff::new(NOTIFY_BACKOFF),
+        }
+    }
+
+    fn shell(&self) -> Command {
+        if cfg!(windows) {
+            let mut cmd = Command::new("cmd");
+            cmd.arg("/C").arg(&self.config.command);
+            cmd
+        } else {
+            let mut cmd = Command::new("sh");
+            cmd.arg("-c").arg(&self.config.command);
+            cmd
+        }
+    }
+}
+
+impl MetricSink for ExecHookSink {
+    /// Like the webhook, a hook that fails to start leaves its alert due and is retried
+    /// after a backoff rather than on every sample.
+    fn send(&mut self, snapshot: &MetricsSnapshot) -> Result<()> {
+        // Reap hooks that finished since the last sample; hooks never block sampling.
+        self.running
+            .retain_mut(|child| matches!(child.try_wait(), Ok(None)));
+
+        let due = self.gate.due(&snapshot.alerts);
+        if due.is_empty() || !self.backoff.ready() {
+            return Ok(());
+        }
+
+        let host = hostname(snapshot);
+        for alert in due {
+            let spawned = self
+                .shell()
+                .env("GIM_ALERT_RULE", &alert.rule)
+                .env("GIM_ALERT_METRIC", alert.path.to_string())
+                .env("GIM_ALERT_SEVERITY", alert.severity_label())
+                .env("GIM_ALERT_STATE", alert.state.label())
+                .env("GIM_ALERT_VALUE", alert.value.to_string())
+                .env("GIM_ALERT_SINCE", unix_secs(&alert).to_string())
+                .env("GIM_HOST", &host)
+                .stdin(Stdio::null())
+                .spawn();
+            match spawned {
+                Ok(child) => self.running.push(child),
+                Err(e) => {
+                    self.backoff.failed();
+                    return Err(sink_error("exec", e.to_string()));
+                }
+            }
+            self.gate.mark_sent(&alert);
+        }
+        self.backoff.succeeded();
+        Ok(())
+    }
+
+    fn name(&self) -> &'static str {
+        "exec"
+    }
+}
+
+fn unix_secs(alert: &Alert) -> u64 {
+    alert
+        .since
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+fn sink_error(sink: &str, msg: String) -> GimError {
+    GimError::Sink {
+        sink: sink.to_string(),
+        source: msg.into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{MetricPath, Severity};
+    use crate::sinks::http::test_server;
+    use serde_json::Value;
+    use std::time::SystemTime;
+
+    fn alert(state: AlertState) -> Alert {
+        Alert {
+            rule: "cpu.cpu_usage_percent > 90 => critical".to_string(),
+            path: MetricPath::parse("cpu.cpu_usage_percent").unwrap(),
+            severity: Severity::Critical,
+            state,
+            value: 97.5,
+            since: SystemTime::now(),
+            changed: true,
+        }
+    }
+
+    fn snapshot(alerts: Vec<Alert>) -> MetricsSnapshot {
+        MetricsSnapshot {
+            modules: Vec::new(),
+            alerts,
+            anomalies: Vec::new(),
+        }
+    }
+
+    fn webhook(port: u16) -> WebhookSink {
+        WebhookSink::new(WebhookConfig {
+            url: format!("http://127.0.0.1:{}/hook", port),
+            cooldown_secs: 0,
+            ..WebhookConfig::default()
+        })
+    }
+
+    #[test]
+    fn posts_alert_transitions() {
+        let (port, requests) = test_server::serve(vec![200, 200]);
+        let mut sink = webhook(port);
+
+        sink.send(&snapshot(vec![alert(AlertState::Firing)]))
+            .unwrap();
+        let request = requests.recv().unwrap();
+        assert!(request.head.starts_with("POST /hook HTTP/1.1\r\n"));
+        let body: Value = serde_json::from_str(&request.body).unwrap();
+        assert_eq!(body["rule"], "cpu.cpu_usage_percent > 90 => critical");
+        assert_eq!(body["metric"], "cpu.cpu_usage_percent");
+        assert_eq!(body["severity"], "critical");
+        assert_eq!(body["state"], "firing");
+        assert_eq!(body["value"], 97.5);
+
+        // Still firing: nothing new to report.
+        sink.send(&snapshot(vec![alert(AlertState::Firing)]))
+            .unwrap();
+        sink.send(&snapshot(vec![alert(AlertState::Resolved)]))
+            .unwrap();
+        let body: Value = serde_json::from_str(&requests.recv().unwrap().body).unwrap();
+        assert_eq!(body["state"], "resolved");
+    }
+
+    #[test]
+    fn backs_off_and_retries_a_failed_delivery() {
+        let (port, requests) = test_server::serve(vec![500, 200]);
+        let mut sink = webhook(port);
+        let firing = || snapshot(vec![alert(AlertState::Firing)]);
+
+        assert!(sink.send(&firing()).is_err());
+        requests.recv().unwrap();
+
+        // Inside the backoff the receiver is left alone and the alert stays due.
+        sink.send(&firing()).unwrap();
+        assert!(requests.recv_timeout(Duration::from_millis(100)).is_err());
+
+        sink.backoff.succeeded();
+        sink.send(&firing()).unwrap();
+        let body: Value = serde_json::from_str(&requests.recv().unwrap().body).unwrap();
+        assert_eq!(body["state"], "firing");
+    }
+
+    #[test]
+    fn gate_holds_back_flapping_within_the_cooldown() {
+        let mut gate = NotifyGate::new(3600);
+        let due = gate.due(&[alert(AlertState::Firing)]);
+        assert_eq!(due.len(), 1);
+        gate.mark_sent(&due[0]);
+
+        assert!(gate.due(&[alert(AlertState::Resolved)]).is_empty());
+        assert!(gate.due(&[alert(AlertState::Pending)]).is_empty());
+        assert!(gate.due(&[alert(AlertState::Firing)]).is_empty());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn runs_the_hook_with_alert_details() {
+        let out = std::env::temp_dir().join(format!("gim-exec-hook-{}", std::process::id()));
+        let mut sink = ExecHookSink::new(ExecHookConfig {
+            command: format!(
+                "echo \"$GIM_ALERT_STATE $GIM_ALERT_SEVERITY\" > {}",
+                out.display()
+            ),
+            cooldown_secs: 0,
+        });
+
+        sink.send(&snapshot(vec![alert(AlertState::Firing)]))
+            .unwrap();
+        for mut child in sink.running.drain(..) {
+            child.wait().unwrap();
+        }
+        assert_eq!(std::fs::read_to_string(&out).unwrap(), "firing critical\n");
+        std::fs::remove_file(&out).unwrap();
+    }
+
+    #[test]
+    fn hook_waits_out_the_backoff() {
+        let mut sink = ExecHookSink::new(ExecHookConfig {
+            command: "true".to_string(),
+            cooldown_secs: 0,
+        });
+        sink.backoff.failed();
+
+        // Held back without spawning anything, and still due afterwards.
+        sink.send(&snapshot(vec![alert(AlertState::Firing)]))
+            .unwrap();
+        assert!(sink.running.is_empty());
+        assert_eq!(sink.gate.due(&[]).len(), 1);
+    }
+}
@@ -0,0 +1,375 @@
+use std::collections::VecDeque;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde_json::{json, Value};
+
+use crate::config::OtlpConfig;
+use crate::core::{MetricKind, MetricValue};
+use crate::engine::MetricsSnapshot;
+use crate::error::{GimError, Result};
+use crate::sinks::{hostname, http, Backoff, MetricSink};
+
+/// Most payloads held for a collector that is down; older ones are dropped first.
+const MAX_PENDING_PAYLOADS: usize = 64;
+
+/// Most payloads posted per sample, so a backlog drains over several samples instead of
+/// holding up the watch loop while the collector is slow.
+const MAX_POSTS_PER_SAMPLE: usize = 4;
+
+pub struct OtlpSink {
+    config: OtlpConfig,
+    headers: Vec<(String, String)>,
+    start_time: SystemTime,
+    /// Payloads the collector hasn't accepted yet, oldest first, with their failed attempts.
+    pending: VecDeque<(Vec<u8>, u32)>,
+    backoff: Backoff,
+}
+
+impl OtlpSink {
+    pub fn new(config: OtlpConfig) -> Self {
+        let headers = config
+            .headers
+            .iter()
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect();
+        Self {
+            backoff: Backoff::new(Duration::from_millis(config.backoff_ms)),
+            config,
+            headers,
+            start_time: SystemTime::now(),
+            pending: VecDeque::new(),
+        }
+    }
+
+    fn payload(&self, snapshot: &MetricsSnapshot) -> Value {
+        let start = unix_nanos(self.start_time);
+        let mut metrics = Vec::new();
+
+        for (module, data) in &snapshot.modules {
+            let time = unix_nanos(data.timestamp);
+            let mut entries: Vec<_> = data.metrics.iter().collect();
+            entries.sort_by_key(|(k, _)| *k);
+
+            for (key, value) in entries {
+                let mut point = match value {
+                    MetricValue::Integer(i) => json!({ "asInt": i.to_string() }),
+                    MetricValue::Boolean(b) => json!({ "asInt": (*b as i64).to_string() }),
+                    MetricValue::Float(f) if f.is_finite() => json!({ "asDouble": f }),
+                    _ => continue,
+                };
+                point["timeUnixNano"] = Value::String(time.clone());
+
+                let mut metric = json!({
+                    "name": self.metric_name(module, key),
+                    "unit": unit_of(key),
+                });
+                match MetricKind::of(key) {
+                    MetricKind::Gauge => {
+                        metric["gauge"] = json!({ "dataPoints": [point] });
+                    }
+                    MetricKind::Counter => {
+                        point["startTimeUnixNano"] = Value::String(start.clone());
+                        metric["sum"] = json!({
+                            "aggregationTemporality": 2,
+                            "isMonotonic": true,
+                            "dataPoints": [point],
+                        });
+                    }
+                }
+                metrics.push(metric);
+            }
+        }
+
+        json!({
+            "resourceMetrics": [{
+                "resource": { "attributes": resource_attributes(snapshot) },
+                "scopeMetrics": [{
+                    "scope": { "name": "gim", "version": env!("CARGO_PKG_VERSION") },
+                    "metrics": metrics,
+                }],
+            }],
+        })
+    }
+
+    fn metric_name(&self, module: &str, key: &str) -> String {
+        if self.config.prefix.is_empty() {
+            format!("{}.{}", module, key)
+        } else {
+            format!("{}.{}.{}", self.config.prefix, module, key)
+        }
+    }
+
+    /// Posts held payloads oldest first, at most `MAX_POSTS_PER_SAMPLE` of them, stopping
+    /// at the first failure.
+    fn flush_pending(&mut self) -> std::result::Result<(), String> {
+        for _ in 0..MAX_POSTS_PER_SAMPLE {
+            let Some((body, _)) = self.pending.front() else {
+                break;
+            };
+            if let Err(e) = self.post(body) {
+                let mut msg = format!("{} ({} payloads pending", e, self.pending.len());
+                self.pending[0].1 += 1;
+                if self.pending[0].1 > self.config.max_retries {
+                    self.pending.pop_front();
+                    msg.push_str(", oldest dropped after all retries");
+                }
+                msg.push(')');
+                self.backoff.failed();
+                return Err(msg);
+            }
+            self.pending.pop_front();
+        }
+        self.backoff.succeeded();
+        Ok(())
+    }
+
+    fn post(&self, body: &[u8]) -> std::result::Result<(), String> {
+        let timeout = Duration::from_millis(self.config.timeout_ms);
+        match http::post(
+            &self.config.endpoint,
+            "application/json",
+            &self.headers,
+            body,
+            timeout,
+        ) {
+            Ok(status) if (200..300).contains(&status) => Ok(()),
+            Ok(status) => Err(format!("collector responded with HTTP {}", status)),
+            Err(e) => Err(e.to_string()),
+        }
+    }
+}
+
+impl MetricSink for OtlpSink {
+    /// Posts the sample along with earlier ones the collector hasn't accepted yet. Failed
+    /// payloads are kept and retried on later samples, after a backoff, up to
+    /// `max_retries` times; the watch loop never sleeps here.
+    fn send(&mut self, snapshot: &MetricsSnapshot) -> Result<()> {
+        let body = serde_json::to_vec(&self.payload(snapshot)).map_err(|e| GimError::Sink {
+            sink: "otlp".to_string(),
+            source: Box::new(e),
+        })?;
+        let overflowed = self.pending.len() >= MAX_PENDING_PAYLOADS;
+        if overflowed {
+            self.pending.pop_front();
+        }
+        self.pending.push_back((body, 0));
+
+        let mut problems = Vec::new();
+        if self.backoff.ready() {
+            if let Err(e) = self.flush_pending() {
+                problems.push(e);
+            }
+        }
+        if overflowed {
+            problems.push(format!(
+                "{} payloads pending, dropped the oldest to make room",
+                MAX_PENDING_PAYLOADS
+            ));
+        }
+        if problems.is_empty() {
+            return Ok(());
+        }
+        Err(GimError::Sink {
+            sink: "otlp".to_string(),
+            source: problems.join("; ").into(),
+        })
+    }
+
+    fn name(&self) -> &'static str {
+        "otlp"
+    }
+}
+
+fn resource_attributes(snapshot: &MetricsSnapshot) -> Vec<Value> {
+    let system = snapshot
+        .modules
+        .iter()
+        .find(|(name, _)| name == "system")
+        .map(|(_, data)| data);
+    let system_string = |key: &str| match system.and_then(|d| d.metrics.get(key)) {
+        Some(MetricValue::String(s)) => Some(s.clone()),
+        _ => None,
+    };
+
+    let os_type = system_string("os_type").unwrap_or_else(|| std::env::consts::OS.to_string());
+    let arch = system_string("arch").unwrap_or_else(sysinfo::System::cpu_arch);
+
+    let attributes = [
+        ("service.name", "gim".to_string()),
+        ("host.name", hostname(snapshot)),
+        ("os.type", otel_os_type(&os_type).to_string()),
+        ("host.arch", otel_arch(&arch).to_string()),
+    ];
+
+    attributes
+        .into_iter()
+        .map(|(key, value)| json!({ "key": key, "value": { "stringValue": value } }))
+        .collect()
+}
+
+/// Maps Rust's OS names onto the OpenTelemetry `os.type` values where they differ.
+fn otel_os_type(os: &str) -> &str {
+    match os {
+        "macos" => "darwin",
+        other => other,
+    }
+}
+
+/// Maps architecture names onto the OpenTelemetry `host.arch` values where they differ.
+fn otel_arch(arch: &str) -> &str {
+    match arch {
+        "x86_64" => "amd64",
+        "aarch64" => "arm64",
+        "x86" | "i386" | "i686" => "x86",
+        "powerpc64" => "ppc64",
+        other => other,
+    }
+}
+
+fn unit_of(key: &str) -> &'static str {
+    if key.ends_with("_percent") {
+        "%"
+    } else if key.ends_with("_bytes") {
+        "By"
+    } else if key.ends_with("_seconds") {
+        "s"
+    } else {
+        "1"
+    }
+}
+
+fn unix_nanos(time: SystemTime) -> String {
+    time.duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or(0)
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::MetricData;
+    use crate::sinks::http::test_server;
+    use std::time::Instant;
+
+    fn snapshot() -> MetricsSnapshot {
+        MetricsSnapshot {
+            modules: vec![(
+                "cpu".to_string(),
+                MetricData {
+                    timestamp: SystemTime::now(),
+                    metrics: [
+                        ("usage_percent".to_string(), MetricValue::Float(12.5)),
+                        ("interrupts_total".to_string(), MetricValue::Integer(7)),
+                        ("brand".to_string(), MetricValue::String("x".to_string())),
+                    ]
+                    .into_iter()
+                    .collect(),
+                },
+            )],
+            alerts: Vec::new(),
+            anomalies: Vec::new(),
+        }
+    }
+
+    fn sink(port: u16, backoff_ms: u64) -> OtlpSink {
+        OtlpSink::new(OtlpConfig {
+            endpoint: format!("http://127.0.0.1:{}/v1/metrics", port),
+            backoff_ms,
+            ..OtlpConfig::default()
+        })
+    }
+
+    #[test]
+    fn posts_gauges_and_sums_as_json() {
+        let (port, requests) = test_server::serve(vec![200]);
+        sink(port, 500).send(&snapshot()).unwrap();
+
+        let request = requests.recv().unwrap();
+        assert!(request.head.starts_with("POST /v1/metrics HTTP/1.1\r\n"));
+        let body: Value = serde_json::from_str(&request.body).unwrap();
+        let metrics = &body["resourceMetrics"][0]["scopeMetrics"][0]["metrics"];
+        assert_eq!(metrics.as_array().unwrap().len(), 2);
+
+        assert_eq!(metrics[0]["name"], "gim.cpu.interrupts_total");
+        assert_eq!(metrics[0]["sum"]["isMonotonic"], true);
+        assert_eq!(metrics[0]["sum"]["dataPoints"][0]["asInt"], "7");
+        assert_eq!(metrics[1]["name"], "gim.cpu.usage_percent");
+        assert_eq!(metrics[1]["unit"], "%");
+        assert_eq!(metrics[1]["gauge"]["dataPoints"][0]["asDouble"], 12.5);
+    }
+
+    #[test]
+    fn failed_payloads_are_retried_on_later_samples() {
+        let (port, requests) = test_server::serve(vec![503, 200, 200]);
+        let mut sink = sink(port, 0);
+
+        let started = Instant::now();
+        assert!(sink.send(&snapshot()).is_err());
+        assert!(started.elapsed() < Duration::from_secs(1));
+        let rejected = requests.recv().unwrap().body;
+
+        // The next sample delivers the held payload first, then its own.
+        sink.send(&snapshot()).unwrap();
+        assert_eq!(requests.recv().unwrap().body, rejected);
+        assert_ne!(requests.recv().unwrap().body, rejected);
+        assert!(sink.pending.is_empty());
+    }
+
+    #[test]
+    fn waits_out_the_backoff_before_retrying() {
+        let (port, requests) = test_server::serve(vec![503]);
+        let mut sink = sink(port, 60_000);
+
+        assert!(sink.send(&snapshot()).is_err());
+        requests.recv().unwrap();
+        // Still inside the backoff: held without contacting the collector.
+        sink.send(&snapshot()).unwrap();
+        assert_eq!(sink.pending.len(), 2);
+    }
+
+    #[test]
+    fn drops_a_payload_after_max_retries() {
+        let (port, _requests) = test_server::serve(vec![503, 503]);
+        let mut sink = OtlpSink::new(OtlpConfig {
+            endpoint: format!("http://127.0.0.1:{}/v1/metrics", port),
+            backoff_ms: 0,
+            max_retries: 1,
+            ..OtlpConfig::default()
+        });
+
+        assert!(sink.send(&snapshot()).is_err());
+        assert_eq!(sink.pending.len(), 1);
+        let err = sink.send(&snapshot()).unwrap_err().to_string();
+        assert!(err.contains("oldest dropped after all retries"), "{}", err);
+        assert_eq!(sink.pending.len(), 1);
+    }
+
+    #[test]
+    fn drains_a_backlog_a_few_payloads_per_sample() {
+        let (port, requests) = test_server::serve(vec![200; MAX_POSTS_PER_SAMPLE]);
+        let mut sink = sink(port, 0);
+        for _ in 0..9 {
+            sink.pending.push_back((b"{}".to_vec(), 0));
+        }
+
+        sink.send(&snapshot()).unwrap();
+        for _ in 0..MAX_POSTS_PER_SAMPLE {
+            requests.recv().unwrap();
+        }
+        assert_eq!(sink.pending.len(), 10 - MAX_POSTS_PER_SAMPLE);
+    }
+
+    #[test]
+    fn reports_payloads_dropped_from_a_full_queue() {
+        let mut sink = sink(1, 60_000);
+        for _ in 0..MAX_PENDING_PAYLOADS {
+            sink.pending.push_back((b"{}".to_vec(), 0));
+        }
+        sink.backoff.failed();
+
+        let err = sink.send(&snapshot()).unwrap_err().to_string();
+        assert!(err.contains("dropped the oldest"), "{}", err);
+        assert_eq!(sink.pending.len(), MAX_PENDING_PAYLOADS);
+    }
+}
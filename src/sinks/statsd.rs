@@ -0,0 +1,244 @@
+use std::collections::HashMap;
+use std::net::{SocketAddr, ToSocketAddrs, UdpSocket};
+
+use crate::config::StatsdConfig;
+use crate::core::MetricKind;
+use crate::engine::MetricsSnapshot;
+use crate::error::{GimError, Result};
+use crate::sinks::{format_number, hostname, numeric_metrics, sanitize, MetricSink};
+
+pub struct StatsdSink {
+    config: StatsdConfig,
+    socket: UdpSocket,
+    addr: SocketAddr,
+    last_counters: HashMap<String, f64>,
+}
+
+impl StatsdSink {
+    pub fn new(config: StatsdConfig) -> Result<Self> {
+        let addr = (config.host.as_str(), config.port)
+            .to_socket_addrs()
+            .map_err(|e| sink_error(Box::new(e)))?
+            .next()
+            .ok_or_else(|| {
+                sink_error(format!("could not resolve {}:{}", config.host, config.port).into())
+            })?;
+
+        let bind_addr = if addr.is_ipv4() {
+            "0.0.0.0:0"
+        } else {
+            "[::]:0"
+        };
+        let socket = UdpSocket::bind(bind_addr)?;
+
+        Ok(Self {
+            config,
+            socket,
+            addr,
+            last_counters: HashMap::new(),
+        })
+    }
+
+    fn lines(&mut self, snapshot: &MetricsSnapshot) -> Vec<String> {
+        let host = hostname(snapshot);
+        let mut lines = Vec::new();
+
+        for (module, data) in &snapshot.modules {
+            for (key, value) in numeric_metrics(data) {
+                let name = self.metric_name(module, key);
+                let (value, kind) = match MetricKind::of(key) {
+                    MetricKind::Gauge => (value, "g"),
+                    MetricKind::Counter => {
+                        // StatsD counters are increments, so send the delta since the last sample.
+                        let previous = self.last_counters.insert(name.clone(), value);
+                        match previous {
+                            Some(prev) if value >= prev => (value - prev, "c"),
+                            _ => continue,
+                        }
+                    }
+                };
+
+                let mut line = format!("{}:{}|{}", name, format_number(value), kind);
+                if self.config.dogstatsd_tags {
+                    line.push_str(&format!(
+                        "|#host:{},module:{}",
+                        sanitize(&host),
+                        sanitize(module)
+                    ));
+                }
+                lines.push(line);
+            }
+        }
+
+        lines
+    }
+
+    fn metric_name(&self, module: &str, key: &str) -> String {
+        if self.config.prefix.is_empty() {
+            format!("{}.{}", sanitize(module), sanitize(key))
+        } else {
+            format!(
+                "{}.{}.{}",
+                sanitize(&self.config.prefix),
+                sanitize(module),
+                sanitize(key)
+            )
+        }
+    }
+}
+
+impl MetricSink for StatsdSink {
+    fn send(&mut self, snapshot: &MetricsSnapshot) -> Result<()> {
+        let lines = self.lines(snapshot);
+        for packet in batch(&lines, self.config.max_packet_bytes) {
+            self.socket.send_to(packet.as_bytes(), self.addr)?;
+        }
+        Ok(())
+    }
+
+    fn name(&self) -> &'static str {
+        "statsd"
+    }
+}
+
+/// Joins lines with newlines into packets no larger than `max_bytes`.
+/// A single line longer than the limit is sent on its own.
+fn batch(lines: &[String], max_bytes: usize) -> Vec<String> {
+    let mut packets = Vec::new();
+    let mut current = String::new();
+
+    for line in lines {
+        if !current.is_empty() && current.len() + 1 + line.len() > max_bytes {
+            packets.push(std::mem::take(&mut current));
+        }
+        if !current.is_empty() {
+            current.push('\n');
+        }
+        current.push_str(line);
+    }
+
+    if !current.is_empty() {
+        packets.push(current);
+    }
+    packets
+}
+
+fn sink_error(source: Box<dyn std::error::Error + Send + Sync>) -> GimError {
+    GimError::Sink {
+        sink: "statsd".to_string(),
+        source,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{MetricData, MetricValue};
+    use std::time::{Duration, SystemTime};
+
+    fn snapshot(metrics: &[(&str, MetricValue)]) -> MetricsSnapshot {
+        MetricsSnapshot {
+            modules: vec![(
+                "network".to_string(),
+                MetricData {
+                    timestamp: SystemTime::now(),
+                    metrics: metrics
+                        .iter()
+                        .map(|(k, v)| (k.to_string(), v.clone()))
+                        .collect(),
+                },
+            )],
+            alerts: Vec::new(),
+            anomalies: Vec::new(),
+        }
+    }
+
+    fn sink(port: u16) -> StatsdSink {
+        StatsdSink::new(StatsdConfig {
+            port,
+            ..StatsdConfig::default()
+        })
+        .unwrap()
+    }
+
+    fn lines_of(n: usize, len: usize) -> Vec<String> {
+        (0..n).map(|i| format!("{:0>len$}", i, len = len)).collect()
+    }
+
+    #[test]
+    fn batch_packs_lines_up_to_the_limit() {
+        let packets = batch(&lines_of(5, 9), 30);
+        // Three 9-byte lines plus two newlines make 29 bytes; a fourth would overflow.
+        assert_eq!(packets.len(), 2);
+        assert_eq!(packets[0], "000000000\n000000001\n000000002");
+        assert_eq!(packets[1], "000000003\n000000004");
+        assert!(packets.iter().all(|p| p.len() <= 30));
+    }
+
+    #[test]
+    fn batch_sends_oversized_lines_alone() {
+        let lines = vec!["a".to_string(), "b".repeat(50), "c".to_string()];
+        assert_eq!(batch(&lines, 10), vec!["a", &"b".repeat(50), "c"]);
+        assert!(batch(&[], 10).is_empty());
+    }
+
+    #[test]
+    fn counters_are_sent_as_deltas() {
+        let mut sink = sink(8125);
+        let sample = |rx: i64| {
+            snapshot(&[
+                ("rx_total", MetricValue::Integer(rx)),
+                ("usage_percent", MetricValue::Float(12.5)),
+            ])
+        };
+
+        // Nothing to compare against yet, so only the gauge goes out.
+        assert_eq!(
+            sink.lines(&sample(100)),
+            vec!["gim.network.usage_percent:12.5|g"]
+        );
+        assert_eq!(
+            sink.lines(&sample(150)),
+            vec![
+                "gim.network.rx_total:50|c",
+                "gim.network.usage_percent:12.5|g"
+            ]
+        );
+        // A counter that went backwards was reset; skip it rather than send a negative.
+        assert_eq!(
+            sink.lines(&sample(10)),
+            vec!["gim.network.usage_percent:12.5|g"]
+        );
+        assert_eq!(
+            sink.lines(&sample(15))[0],
+            "gim.network.rx_total:5|c".to_string()
+        );
+    }
+
+    #[test]
+    fn sends_packets_over_udp() {
+        let receiver = UdpSocket::bind("127.0.0.1:0").unwrap();
+        receiver
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        let mut sink = StatsdSink::new(StatsdConfig {
+            port: receiver.local_addr().unwrap().port(),
+            dogstatsd_tags: true,
+            ..StatsdConfig::default()
+        })
+        .unwrap();
+
+        sink.send(&snapshot(&[("usage_percent", MetricValue::Float(42.0))]))
+            .unwrap();
+
+        let mut buf = [0u8; 1500];
+        let n = receiver.recv(&mut buf).unwrap();
+        let packet = std::str::from_utf8(&buf[..n]).unwrap();
+        assert!(
+            packet.starts_with("gim.network.usage_percent:42|g|#host:"),
+            "{}",
+            packet
+        );
+        assert!(packet.ends_with(",module:network"), "{}", packet);
+    }
+}
@@ -13,8 +13,10 @@ use ratatui::text::{Line, Span};
 use ratatui::widgets::{Block, Borders, Gauge, Paragraph, Wrap};
 use ratatui::Terminal;
 
-use crate::config::{parse_color, BorderStyle, Config, ModuleTheme};
-use crate::core::MetricValue;
+use crate::alerts::AlertState;
+use crate::anomaly::is_flagged;
+use crate::config::{parse_color, BorderStyle, Config};
+use crate::core::{MetricValue, Severity};
 use crate::engine::{Engine, MetricsSnapshot};
 use crate::error::Result;
 
@@ -128,18 +130,82 @@ fn draw_ui(frame: &mut ratatui::Frame, app: &App) {
         BorderStyle::Rounded => ratatui::widgets::BorderType::Rounded,
     };
 
+    let alert_count = app.snapshot.as_ref().map(|s| s.alerts.len()).unwrap_or(0);
+    let alerts_height = if alert_count == 0 {
+        0
+    } else {
+        alert_count.min(5) as u16 + 2
+    };
+
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
             Constraint::Length(3),
             Constraint::Min(10),
+            Constraint::Length(alerts_height),
             Constraint::Length(3),
         ])
         .split(area);
 
     draw_header(frame, chunks[0], app, chrome_border, chrome_title, border_type);
     draw_modules(frame, chunks[1], app, border_type);
-    draw_footer(frame, chunks[2], app, chrome_border, border_type);
+    draw_alerts(frame, chunks[2], app, chrome_border, border_type);
+    draw_footer(frame, chunks[3], app, chrome_border, border_type);
+}
+
+fn draw_alerts(
+    frame: &mut ratatui::Frame,
+    area: Rect,
+    app: &App,
+    border_color: ratatui::style::Color,
+    border_type: ratatui::widgets::BorderType,
+) {
+    let alerts = match &app.snapshot {
+        Some(s) if !s.alerts.is_empty() => &s.alerts,
+        _ => return,
+    };
+
+    let chrome = &app.config.theme.chrome;
+    let lines: Vec<Line> = alerts
+        .iter()
+        .map(|alert| {
+            let color = match (alert.state, alert.severity) {
+                (AlertState::Resolved, _) | (_, Severity::Ok) => parse_color(&chrome.header),
+                (_, Severity::Warning) => parse_color(&chrome.warning),
+                (_, Severity::Critical) => parse_color(&chrome.error),
+            };
+            let mut style = Style::default().fg(color);
+            if alert.state == AlertState::Firing {
+                style = style.add_modifier(Modifier::BOLD);
+            }
+
+            Line::from(vec![
+                Span::styled(
+                    format!(
+                        "{:<8} {:<8} ",
+                        alert.severity_label().to_uppercase(),
+                        alert.state.label()
+                    ),
+                    style,
+                ),
+                Span::raw(format!("{:.2}  {}", alert.value, alert.rule)),
+            ])
+        })
+        .collect();
+
+    let panel = Paragraph::new(lines).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_type(border_type)
+            .border_style(Style::default().fg(border_color))
+            .title(Span::styled(
+                " Alerts ",
+                Style::default()
+                    .fg(parse_color(&chrome.error))
+                    .add_modifier(Modifier::BOLD),
+            )),
+    );
+    frame.render_widget(panel, area);
 }
 
 fn draw_header(
@@ -277,17 +343,45 @@ fn draw_modules(
             frame.render_widget(gauge, inner_chunks[0]);
         }
 
+        if name == "cgroups" {
+            let table = Paragraph::new(cgroup_lines(data, fg));
+            frame.render_widget(table, inner_chunks[1]);
+            continue;
+        }
+
         let mut lines: Vec<Line> = Vec::new();
         let mut entries: Vec<_> = data.metrics.iter().collect();
-        entries.sort_by_key(|(k, _)| k.clone());
+        entries.sort_by_key(|(k, _)| *k);
 
         for (key, value) in entries {
+            let value_span = if is_flagged(&snapshot.anomalies, name, key) {
+                Span::styled(
+                    format!("{} (anomaly)", metric_display(key, value)),
+                    Style::default()
+                        .fg(parse_color(&app.config.theme.chrome.warning))
+                        .add_modifier(Modifier::BOLD | Modifier::REVERSED),
+                )
+            } else {
+                match value_severity(&app.config, name, data, key, value) {
+                    Severity::Ok => Span::raw(metric_display(key, value)),
+                    Severity::Warning => Span::styled(
+                        metric_display(key, value),
+                        Style::default().fg(parse_color(&app.config.theme.chrome.warning)),
+                    ),
+                    Severity::Critical => Span::styled(
+                        metric_display(key, value),
+                        Style::default()
+                            .fg(parse_color(&app.config.theme.chrome.error))
+                            .add_modifier(Modifier::BOLD),
+                    ),
+                }
+            };
             lines.push(Line::from(vec![
                 Span::styled(
                     format!("{}: ", key),
                     Style::default().fg(fg).add_modifier(Modifier::BOLD),
                 ),
-                Span::raw(metric_display(value)),
+                value_span,
             ]));
         }
 
@@ -296,6 +390,81 @@ fn draw_modules(
     }
 }
 
+/// One row per ranked cgroup, busiest CPU first, instead of the raw key/value list.
+fn cgroup_lines(data: &crate::core::MetricData, fg: ratatui::style::Color) -> Vec<Line<'static>> {
+    let field = |path: &str, name: &str| {
+        data.metrics
+            .get(&format!("cgroup.{}.{}", path, name))
+            .and_then(MetricValue::as_f64)
+    };
+
+    let mut paths: Vec<&str> = data
+        .metrics
+        .keys()
+        .filter_map(|key| {
+            key.strip_prefix("cgroup.")?
+                .strip_suffix(".cpu_usage_cores")
+        })
+        .collect();
+    paths.sort_by(|a, b| {
+        let cpu = |path: &str| field(path, "cpu_usage_cores").unwrap_or(0.0);
+        cpu(b).total_cmp(&cpu(a)).then(a.cmp(b))
+    });
+
+    let mut lines = vec![Line::from(Span::styled(
+        format!("{:>6} {:>10} {:>12}  {}", "CPU", "MEMORY", "IO/s", "CGROUP"),
+        Style::default().fg(fg).add_modifier(Modifier::BOLD),
+    ))];
+    for path in paths {
+        let memory = field(path, "memory_current_bytes")
+            .map(|b| format_bytes_smart(b as i64))
+            .unwrap_or_else(|| "-".to_string());
+        let io = format_bytes_smart(field(path, "io_bytes_per_sec").unwrap_or(0.0) as i64);
+        lines.push(Line::from(format!(
+            "{:>6.2} {:>10} {:>12}  {}",
+            field(path, "cpu_usage_cores").unwrap_or(0.0),
+            memory,
+            io,
+            path
+        )));
+    }
+    lines
+}
+
+fn value_severity(
+    config: &Config,
+    module: &str,
+    data: &crate::core::MetricData,
+    key: &str,
+    value: &MetricValue,
+) -> Severity {
+    let Some(v) = value.as_f64() else {
+        return Severity::Ok;
+    };
+    if let Some(threshold) = config.print.threshold_for(module, key) {
+        return threshold.severity(v);
+    }
+
+    // Temperatures are judged against each sensor's own critical point.
+    if module == "sensors" {
+        let critical = key
+            .strip_suffix(".temperature_celsius")
+            .and_then(|sensor| data.metrics.get(&format!("{}.critical_celsius", sensor)))
+            .and_then(MetricValue::as_f64);
+        if let Some(critical) = critical {
+            return if v >= critical {
+                Severity::Critical
+            } else if v >= critical - config.modules.sensors.warning_margin_celsius {
+                Severity::Warning
+            } else {
+                Severity::Ok
+            };
+        }
+    }
+
+    Severity::Ok
+}
+
 struct GaugeData {
     label: String,
     ratio: f64,
@@ -306,6 +475,7 @@ fn extract_gauge(module_name: &str, data: &crate::core::MetricData) -> Option<Ga
         "cpu" => "cpu_usage_percent",
         "memory" => "memory_usage_percent",
         "disk" => "usage_percent",
+        "pressure" => "max_some_avg10",
         _ => return None,
     };
 
@@ -318,15 +488,16 @@ fn extract_gauge(module_name: &str, data: &crate::core::MetricData) -> Option<Ga
     })
 }
 
-fn metric_display(value: &MetricValue) -> String {
+fn metric_display(key: &str, value: &MetricValue) -> String {
     match value {
-        MetricValue::Integer(i) => format_bytes_smart(*i),
+        MetricValue::Integer(i) if key.contains("bytes") => format_bytes_smart(*i),
+        MetricValue::Integer(i) => i.to_string(),
         MetricValue::Float(f) => format!("{:.2}", f),
         MetricValue::String(s) => s.clone(),
         MetricValue::Boolean(b) => b.to_string(),
         MetricValue::List(items) => items
             .iter()
-            .map(metric_display)
+            .map(|item| metric_display(key, item))
             .collect::<Vec<_>>()
             .join(", "),
     }
@@ -345,28 +516,16 @@ fn format_bytes_smart(value: i64) -> String {
     }
 }
 
-fn module_theme<'a>(config: &'a Config, name: &str) -> &'a ModuleTheme {
-    match name {
-        "cpu" => &config.theme.cpu,
-        "memory" => &config.theme.memory,
-        "disk" => &config.theme.disk,
-        "network" => &config.theme.network,
-        "process" => &config.theme.process,
-        "system" => &config.theme.system,
-        _ => &config.theme.cpu,
-    }
-}
-
 fn module_fg_color(config: &Config, name: &str) -> ratatui::style::Color {
-    parse_color(&module_theme(config, name).fg)
+    parse_color(&config.theme.module(name).fg)
 }
 
 fn module_accent_color(config: &Config, name: &str) -> ratatui::style::Color {
-    parse_color(&module_theme(config, name).accent)
+    parse_color(&config.theme.module(name).accent)
 }
 
 fn module_label(config: &Config, name: &str) -> String {
-    let label = &module_theme(config, name).label;
+    let label = &config.theme.module(name).label;
     if label.is_empty() {
         name.to_uppercase()
     } else {